@@ -0,0 +1,93 @@
+use image::RgbaImage;
+
+// ticks on the horizontal axis are told apart by their x, on the vertical axis by their y
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+// closer "ticks" are more likely anti-aliasing or text than tick marks
+static MIN_TICK_SPACING_PX: usize = 5;
+// the profile has to correlate with its shifted self at least this well to count as periodic
+static MIN_PERIODICITY: f32 = 0.3;
+// shorter spacings within this fraction of the best one win, so that every other tick
+// isn't mistaken for the period
+static SPACING_TOLERANCE: f32 = 0.9;
+
+// OCR-free tick detection: tick marks across the axis show up as evenly spaced peaks in
+// the contrast between neighbouring columns (rows for the vertical axis), so the spacing
+// is the strongest peak of that profile's autocorrelation. Returns the spacing and the
+// position of the first tick in pixels along the axis, the ticks are at
+// `first + k * spacing`. None when nothing periodic stands out.
+#[must_use]
+pub fn detect_axis_scale(image: &RgbaImage, axis: Axis) -> Option<(f32, f32)> {
+    let profile = contrast_profile(image, axis);
+    let n = profile.len();
+    if n < 2 * MIN_TICK_SPACING_PX {
+        return None;
+    }
+    let mean = profile.iter().sum::<f32>() / n as f32;
+    let centred = profile.iter().map(|v| v - mean).collect::<Vec<_>>();
+    let variance = centred.iter().map(|v| v * v).sum::<f32>();
+    if variance <= f32::EPSILON {
+        return None;
+    }
+    let correlations = (MIN_TICK_SPACING_PX..n / 2)
+        .map(|lag| {
+            let correlation = centred
+                .iter()
+                .zip(&centred[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f32>();
+            (lag, correlation / variance)
+        })
+        .collect::<Vec<_>>();
+    let best = correlations
+        .iter()
+        .map(|(_, c)| *c)
+        .fold(f32::NEG_INFINITY, f32::max);
+    if best < MIN_PERIODICITY {
+        return None;
+    }
+    let (spacing, _) = correlations
+        .into_iter()
+        .find(|(_, c)| *c >= SPACING_TOLERANCE * best)?;
+
+    // the phase whose every `spacing`-th sample has the highest average contrast
+    let first = (0..spacing)
+        .map(|phase| {
+            let samples = profile.iter().skip(phase).step_by(spacing);
+            let count = samples.clone().count().max(1) as f32;
+            (phase, samples.sum::<f32>() / count)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(phase, _)| phase)?;
+    Some((spacing as f32, first as f32))
+}
+
+// summed luminance difference between every column and the next one (rows for the
+// vertical axis), high where a tick mark crosses the axis
+fn contrast_profile(image: &RgbaImage, axis: Axis) -> Vec<f32> {
+    let luminance = |x: u32, y: u32| {
+        let [r, g, b, _] = image.get_pixel(x, y).0;
+        (r as f32 + g as f32 + b as f32) / 3.0
+    };
+    let (width, height) = image.dimensions();
+    match axis {
+        Axis::Horizontal => (0..width.saturating_sub(1))
+            .map(|x| {
+                (0..height)
+                    .map(|y| (luminance(x + 1, y) - luminance(x, y)).abs())
+                    .sum()
+            })
+            .collect(),
+        Axis::Vertical => (0..height.saturating_sub(1))
+            .map(|y| {
+                (0..width)
+                    .map(|x| (luminance(x, y + 1) - luminance(x, y)).abs())
+                    .sum()
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fmt::Display, io};
+
+// Errors of the headless API. The UI only ever shows them in the status bar, so the
+// variants carry a readable message rather than structured data.
+#[derive(Debug)]
+// the variant names mirror the kinds of failure, the shared suffix is intended
+#[allow(clippy::enum_variant_names)]
+pub enum SciShotError {
+    // the points can't define a transform, e.g. they coincide on screen
+    CalibrationError(String),
+    FitError(String),
+    IoError(io::Error),
+    ParseError(String),
+}
+
+impl Display for SciShotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SciShotError::CalibrationError(msg) => write!(f, "calibration failed: {}", msg),
+            SciShotError::FitError(msg) => write!(f, "fit failed: {}", msg),
+            SciShotError::IoError(e) => e.fmt(f),
+            SciShotError::ParseError(msg) => write!(f, "parse error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SciShotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SciShotError::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SciShotError {
+    fn from(e: io::Error) -> Self {
+        SciShotError::IoError(e)
+    }
+}
@@ -0,0 +1,603 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui::ColorImage;
+
+use crate::error::SciShotError;
+use crate::io_utils::write_atomic;
+use crate::point_handling::{
+    IntersectionRow, MeasurementResult, PointCoords, PointTransform, RGBColor, ScreenLineSegment,
+    ToCsvRow, Transformable,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    // needs the serde feature
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Csv, ExportFormat::Json];
+
+    #[must_use]
+    #[inline]
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+// which coordinates of a line's points go into its CSV
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CsvFormat {
+    RawOnly,
+    WorldOnly,
+    #[default]
+    Both,
+}
+
+impl CsvFormat {
+    pub const ALL: [CsvFormat; 3] = [CsvFormat::RawOnly, CsvFormat::WorldOnly, CsvFormat::Both];
+
+    #[must_use]
+    #[inline]
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CsvFormat::RawOnly => "Screen",
+            CsvFormat::WorldOnly => "World",
+            CsvFormat::Both => "Screen and world",
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn header(&self) -> &'static str {
+        match self {
+            CsvFormat::RawOnly => "screen_x,screen_y",
+            CsvFormat::WorldOnly => "world_x,world_y",
+            CsvFormat::Both => "screen_x,screen_y,world_x,world_y",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    pub include_headers: bool,
+    pub timestamp_suffix: bool,
+    // CSV only, JSON files are always rewritten
+    pub append: bool,
+    // also write the line's transform to `<name>.transform.json`, needs the serde feature
+    pub transform_sidecar: bool,
+    // lines are written as `resample_points` samples of the fit instead of the clicked points
+    pub resample: bool,
+    pub resample_points: usize,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            format: ExportFormat::Csv,
+            include_headers: false,
+            timestamp_suffix: false,
+            append: false,
+            transform_sidecar: false,
+            resample: false,
+            resample_points: 100,
+        }
+    }
+}
+
+pub enum IoRequest {
+    SaveLine {
+        // `ScreenLineSegment::id` of the saved line
+        line_id: u64,
+        points: Vec<PointCoords>,
+        world_points: Vec<PointCoords>,
+        // maps `points` to `world_points`
+        transform: PointTransform,
+        csv_format: CsvFormat,
+        file_path: PathBuf,
+        settings: ExportSettings,
+    },
+    // every line in one file, see `export_all_lines_json` for the JSON layout
+    SaveAllLines {
+        lines: Vec<ScreenLineSegment>,
+        transform: PointTransform,
+        file_path: PathBuf,
+        settings: ExportSettings,
+    },
+    SaveMeasurements {
+        measurements: Vec<MeasurementResult>,
+        file_path: PathBuf,
+        settings: ExportSettings,
+    },
+    SaveIntersections {
+        rows: Vec<IntersectionRow>,
+        file_path: PathBuf,
+        settings: ExportSettings,
+    },
+    LoadCsv {
+        file_path: PathBuf,
+        color: RGBColor,
+    },
+    // a capture of the app window
+    SaveView {
+        image: Arc<ColorImage>,
+        file_path: PathBuf,
+    },
+}
+
+pub enum IoResponse {
+    // `line_id` is set when the request saved a single line
+    Saved {
+        file_path: PathBuf,
+        line_id: Option<u64>,
+    },
+    // points are in world coordinates, exactly as read from the file
+    Loaded {
+        file_path: PathBuf,
+        points: Vec<PointCoords>,
+        color: RGBColor,
+    },
+    Failed {
+        file_path: PathBuf,
+        line_id: Option<u64>,
+        error: SciShotError,
+    },
+}
+
+impl IoRequest {
+    pub fn file_path(&self) -> &Path {
+        match self {
+            IoRequest::SaveLine { file_path, .. }
+            | IoRequest::SaveAllLines { file_path, .. }
+            | IoRequest::SaveMeasurements { file_path, .. }
+            | IoRequest::SaveIntersections { file_path, .. }
+            | IoRequest::LoadCsv { file_path, .. }
+            | IoRequest::SaveView { file_path, .. } => file_path,
+        }
+    }
+
+    pub fn line_id(&self) -> Option<u64> {
+        match self {
+            IoRequest::SaveLine { line_id, .. } => Some(*line_id),
+            IoRequest::SaveAllLines { .. }
+            | IoRequest::SaveMeasurements { .. }
+            | IoRequest::SaveIntersections { .. }
+            | IoRequest::LoadCsv { .. }
+            | IoRequest::SaveView { .. } => None,
+        }
+    }
+}
+
+// spawns a worker thread so that disk access never blocks the UI
+pub fn spawn_io_thread() -> (Sender<IoRequest>, Receiver<IoResponse>) {
+    let (request_tx, request_rx) = mpsc::channel::<IoRequest>();
+    let (response_tx, response_rx) = mpsc::channel::<IoResponse>();
+    thread::spawn(move || {
+        for request in request_rx {
+            let file_path = request.file_path().to_path_buf();
+            let line_id = request.line_id();
+            let result = match request {
+                IoRequest::SaveLine {
+                    points,
+                    world_points,
+                    transform,
+                    csv_format,
+                    settings,
+                    ..
+                } => write_line_rows(&file_path, &points, &world_points, csv_format, &settings)
+                    .and_then(|saved_path| {
+                        if settings.transform_sidecar {
+                            write_transform_sidecar(&saved_path, &transform)?;
+                        }
+                        Ok(saved_path)
+                    })
+                    .map(|saved_path| IoResponse::Saved {
+                        file_path: saved_path,
+                        line_id,
+                    })
+                    .map_err(SciShotError::from),
+                IoRequest::SaveAllLines {
+                    lines,
+                    transform,
+                    settings,
+                    ..
+                } => {
+                    let saved_path = output_path(&file_path, &settings);
+                    match settings.format {
+                        ExportFormat::Csv => {
+                            write_all_lines_csv(&lines, &transform, &saved_path).map(|_| ())
+                        }
+                        ExportFormat::Json => {
+                            export_all_lines_json(&lines, &transform, &saved_path)
+                        }
+                    }
+                    .map(|()| IoResponse::Saved {
+                        file_path: saved_path,
+                        line_id: None,
+                    })
+                }
+                IoRequest::SaveMeasurements {
+                    measurements,
+                    settings,
+                    ..
+                } => write_rows(
+                    &file_path,
+                    "kind,world_value,screen_x,screen_y",
+                    &measurements,
+                    &settings,
+                )
+                .map(|saved_path| IoResponse::Saved {
+                    file_path: saved_path,
+                    line_id: None,
+                })
+                .map_err(SciShotError::from),
+                IoRequest::SaveIntersections { rows, settings, .. } => write_rows(
+                    &file_path,
+                    "line_a,line_b,world_x,world_y",
+                    &rows,
+                    &settings,
+                )
+                .map(|saved_path| IoResponse::Saved {
+                    file_path: saved_path,
+                    line_id: None,
+                })
+                .map_err(SciShotError::from),
+                IoRequest::LoadCsv { color, .. } => {
+                    read_points(&file_path).map(|points| IoResponse::Loaded {
+                        file_path: file_path.clone(),
+                        points,
+                        color,
+                    })
+                }
+                IoRequest::SaveView { image, .. } => write_png(&file_path, &image)
+                    .map(|()| IoResponse::Saved {
+                        file_path: file_path.clone(),
+                        line_id: None,
+                    })
+                    .map_err(SciShotError::from),
+            };
+            let response = result.unwrap_or_else(|e| IoResponse::Failed {
+                file_path,
+                line_id,
+                error: e,
+            });
+            // the app owns the receiver, once it is gone there is nobody left to notify
+            if response_tx.send(response).is_err() {
+                break;
+            }
+        }
+    });
+    (request_tx, response_rx)
+}
+
+// the path a save to `file_path` ends up at with these settings
+#[must_use]
+pub fn output_path(file_path: &Path, settings: &ExportSettings) -> PathBuf {
+    if settings.timestamp_suffix {
+        with_timestamp_suffix(file_path)
+    } else {
+        file_path.to_path_buf()
+    }
+}
+
+fn with_timestamp_suffix(file_path: &Path) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut file_name = format!("{}_{}", stem, timestamp);
+    if let Some(extension) = file_path.extension() {
+        file_name = format!("{}.{}", file_name, extension.to_string_lossy());
+    }
+    file_path.with_file_name(file_name)
+}
+
+// line CSVs always start with a header naming their columns, so they can be told apart when
+// loaded again
+fn write_line_rows(
+    file_path: &Path,
+    points: &[PointCoords],
+    world_points: &[PointCoords],
+    csv_format: CsvFormat,
+    settings: &ExportSettings,
+) -> io::Result<PathBuf> {
+    let settings = ExportSettings {
+        include_headers: true,
+        ..*settings
+    };
+    let header = csv_format.header();
+    match csv_format {
+        CsvFormat::RawOnly => write_rows(file_path, header, points, &settings),
+        CsvFormat::WorldOnly => write_rows(file_path, header, world_points, &settings),
+        CsvFormat::Both => {
+            let rows = points
+                .iter()
+                .copied()
+                .zip(world_points.iter().copied())
+                .collect::<Vec<_>>();
+            write_rows(file_path, header, &rows, &settings)
+        }
+    }
+}
+
+fn write_rows<T: ToCsvRow>(
+    file_path: &Path,
+    header: &str,
+    rows: &[T],
+    settings: &ExportSettings,
+) -> io::Result<PathBuf> {
+    let file_path = output_path(file_path, settings);
+    // appending rewrites the whole file too, so that it is replaced in one step
+    let mut writer = if settings.append && settings.format == ExportFormat::Csv {
+        match fs::read(&file_path) {
+            Ok(existing) => existing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        }
+    } else {
+        Vec::new()
+    };
+    // when appending, only a fresh file gets a header
+    let is_empty = writer.is_empty();
+    match settings.format {
+        ExportFormat::Csv => {
+            if settings.include_headers && is_empty {
+                writeln!(writer, "{}", header)?;
+            }
+            for row in rows {
+                writeln!(writer, "{}", row.to_csv_row())?;
+            }
+        }
+        ExportFormat::Json => write_json_rows(&mut writer, header, rows)?,
+    }
+    write_atomic(&file_path, &writer)?;
+    Ok(file_path)
+}
+
+// one object per row, keyed by the header's column names, numeric cells become numbers
+#[cfg(feature = "serde")]
+fn write_json_rows<T: ToCsvRow>(
+    writer: &mut impl Write,
+    header: &str,
+    rows: &[T],
+) -> io::Result<()> {
+    use serde_json::Value;
+
+    let objects = rows
+        .iter()
+        .map(|row| {
+            let cells = header
+                .split(',')
+                .zip(row.to_csv_row().split(','))
+                .map(|(column, cell)| {
+                    let value = cell
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map_or_else(|| Value::String(cell.to_owned()), Value::Number);
+                    (column.to_owned(), value)
+                })
+                .collect();
+            Value::Object(cells)
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *writer, &objects).map_err(io::Error::other)?;
+    writeln!(writer)
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_json_rows<T: ToCsvRow>(
+    _writer: &mut impl Write,
+    _header: &str,
+    _rows: &[T],
+) -> io::Result<()> {
+    Err(json_unsupported())
+}
+
+#[cfg(not(feature = "serde"))]
+fn json_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "JSON export needs the serde feature",
+    )
+}
+
+// the points of all lines in one table, with a column naming the line
+fn write_all_lines_csv(
+    lines: &[ScreenLineSegment],
+    transform: &PointTransform,
+    file_path: &Path,
+) -> Result<PathBuf, SciShotError> {
+    let rows = lines
+        .iter()
+        .flat_map(|line| {
+            line.raw_point_coords().into_iter().map(|p| LinePointRow {
+                line: &line.name,
+                screen: p,
+                world: p.transform(transform),
+            })
+        })
+        .collect::<Vec<_>>();
+    let settings = ExportSettings {
+        include_headers: true,
+        ..ExportSettings::default()
+    };
+    let header = "line,screen_x,screen_y,world_x,world_y";
+    Ok(write_rows(file_path, header, &rows, &settings)?)
+}
+
+struct LinePointRow<'a> {
+    line: &'a str,
+    screen: PointCoords,
+    world: PointCoords,
+}
+
+impl ToCsvRow for LinePointRow<'_> {
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.line,
+            self.screen.to_csv_row(),
+            self.world.to_csv_row()
+        )
+    }
+}
+
+// Writes every line as `{id, name, color: {r,g,b}, equation, slope, intercept, r_squared,
+// points: [{screen_x, screen_y, world_x, world_y}]}`, fitted again in the world space of
+// `transform`. Slope and intercept are those of the linearised model.
+#[cfg(feature = "serde")]
+pub fn export_all_lines_json(
+    lines: &[ScreenLineSegment],
+    transform: &PointTransform,
+    path: &Path,
+) -> Result<(), SciShotError> {
+    use serde_json::json;
+
+    let lines = lines
+        .iter()
+        .enumerate()
+        .map(|(id, line)| {
+            let mut line = line.clone();
+            line.transform_line(transform);
+            let points = line
+                .raw_point_coords()
+                .into_iter()
+                .zip(line.raw_point_coords_world())
+                .map(|(screen, world)| {
+                    json!({
+                        "screen_x": screen.x.into_inner(),
+                        "screen_y": screen.y.into_inner(),
+                        "world_x": world.x.into_inner(),
+                        "world_y": world.y.into_inner(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "id": id,
+                "name": line.name,
+                "color": line.draw_color,
+                "equation": line.transformed_line_equation(),
+                "slope": line.world_slope(),
+                "intercept": line.world_intercept(),
+                "r_squared": line.r_squared(),
+                "points": points,
+            })
+        })
+        .collect::<Vec<_>>();
+    let json = serde_json::to_string_pretty(&lines).map_err(io::Error::other)?;
+    write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn export_all_lines_json(
+    _lines: &[ScreenLineSegment],
+    _transform: &PointTransform,
+    _path: &Path,
+) -> Result<(), SciShotError> {
+    Err(json_unsupported().into())
+}
+
+#[cfg(feature = "serde")]
+fn write_transform_sidecar(csv_path: &Path, transform: &PointTransform) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(transform).map_err(io::Error::other)?;
+    write_atomic(&csv_path.with_extension("transform.json"), json.as_bytes())
+}
+
+// reads a transform written by `write_transform_sidecar`
+#[cfg(feature = "serde")]
+pub fn read_transform(path: &Path) -> Result<PointTransform, SciShotError> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| SciShotError::ParseError(e.to_string()))
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_transform_sidecar(_csv_path: &Path, _transform: &PointTransform) -> io::Result<()> {
+    Err(sidecar_unsupported())
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn read_transform(_path: &Path) -> Result<PointTransform, SciShotError> {
+    Err(sidecar_unsupported().into())
+}
+
+#[cfg(not(feature = "serde"))]
+fn sidecar_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "transform side-cars need the serde feature",
+    )
+}
+
+fn write_png(file_path: &Path, image: &ColorImage) -> io::Result<()> {
+    let [width, height] = image.size;
+    let pixels = image
+        .pixels
+        .iter()
+        .flat_map(|c| c.to_srgba_unmultiplied())
+        .collect();
+    // the buffer length always matches, the pixels came from an image of this size
+    let buffer = image::RgbaImage::from_raw(width as u32, height as u32, pixels).unwrap();
+    let format = image::ImageFormat::from_path(file_path).map_err(io::Error::other)?;
+    let mut encoded = io::Cursor::new(Vec::new());
+    buffer
+        .write_to(&mut encoded, format)
+        .map_err(io::Error::other)?;
+    write_atomic(file_path, encoded.get_ref())
+}
+
+// reads `x,y` rows, skipping anything that doesn't parse (e.g. a header), when a header names
+// `world_x` and `world_y` columns those are read instead of the first two
+fn read_points(file_path: &Path) -> Result<Vec<PointCoords>, SciShotError> {
+    let contents = fs::read_to_string(file_path)?;
+    let (x_col, y_col) = contents
+        .lines()
+        .next()
+        .and_then(|header| {
+            let columns = header.split(',').map(str::trim).collect::<Vec<_>>();
+            let x_col = columns.iter().position(|c| *c == "world_x")?;
+            let y_col = columns.iter().position(|c| *c == "world_y")?;
+            Some((x_col, y_col))
+        })
+        .unwrap_or((0, 1));
+    let points = contents
+        .lines()
+        .filter_map(|line| {
+            let columns = line.split(',').collect::<Vec<_>>();
+            let x = columns.get(x_col)?.trim().parse::<f32>().ok()?;
+            let y = columns.get(y_col)?.trim().parse::<f32>().ok()?;
+            Some(PointCoords::new(x, y))
+        })
+        .collect::<Vec<_>>();
+    if points.len() < 2 {
+        return Err(SciShotError::ParseError(
+            "need at least two numeric x,y rows".to_owned(),
+        ));
+    }
+    Ok(points)
+}
@@ -0,0 +1,40 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+// Writes `data` to `<path>.tmp` and renames it over `path`, so readers (and a crash halfway
+// through) see either the old file or the complete new one, never a partial write.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = tmp_path(path);
+    let result = File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        // the target is untouched, only the half-written copy is left to clean up
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+// creates `dir` if needed and checks that files can be written to it
+pub fn ensure_writable_dir(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(".sci-shot-write-test");
+    write_atomic(&probe, b"")?;
+    fs::remove_file(probe)
+}
+
+#[must_use]
+pub fn tmp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,18 @@
+// Headless part of sci-shot: point handling, fitting and file IO. The screenshot UI
+// lives in the binary, behind the `gui` feature.
+pub mod axis_detection;
+pub mod error;
+pub mod export;
+pub mod io_utils;
+pub mod line_set;
+pub mod point_handling;
+pub mod undo;
+
+pub use error::SciShotError;
+pub use export::{CsvFormat, ExportFormat, ExportSettings, IoRequest, IoResponse};
+pub use line_set::LineSet;
+pub use point_handling::{
+    CurveFitKind, FitSpace, MeasurementResult, PointCoords, PointTransform, RGBColor,
+    RegressionLineSegment, ScreenLineSegment, ToFaerMatrix, Transformable, UniquePointBuf,
+};
+pub use undo::{UndoAction, UndoStack};
@@ -0,0 +1,179 @@
+use std::{
+    ops::{Index, IndexMut},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    export::{CsvFormat, ExportFormat, ExportSettings, IoRequest},
+    point_handling::{
+        all_world_points, grid_intersections, CurveFitKind, IntersectionRow, PointCoords,
+        PointTransform, ScreenLineSegment,
+    },
+};
+
+// The regression lines of a session. Lines are addressed by their position, which
+// shifts when an earlier line is removed, or by their name, which is unique.
+#[derive(Clone, Default)]
+pub struct LineSet(Vec<ScreenLineSegment>);
+
+impl LineSet {
+    // appends the line, giving it the first free `line_<n>` name if it has none
+    pub fn add(&mut self, mut line: ScreenLineSegment) -> usize {
+        if line.name.is_empty() || self.by_name(&line.name).is_some() {
+            line.name = (0..)
+                .map(|n| format!("line_{}", n))
+                .find(|name| self.by_name(name).is_none())
+                .unwrap();
+        }
+        self.0.push(line);
+        self.0.len() - 1
+    }
+
+    pub fn remove(&mut self, idx: usize) -> ScreenLineSegment {
+        self.0.remove(idx)
+    }
+
+    pub fn retain(&mut self, keep: impl FnMut(&ScreenLineSegment) -> bool) {
+        self.0.retain(keep);
+    }
+
+    #[must_use]
+    pub fn by_name(&self, name: &str) -> Option<&ScreenLineSegment> {
+        self.0.iter().find(|line| line.name == name)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ScreenLineSegment> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ScreenLineSegment> {
+        self.0.iter_mut()
+    }
+
+    // lines that are drawn, hidden ones are still exported
+    pub fn iter_visible(&self) -> impl Iterator<Item = &ScreenLineSegment> {
+        self.0.iter().filter(|line| line.visible)
+    }
+
+    #[must_use]
+    pub fn all_world_points(&self) -> Vec<PointCoords> {
+        all_world_points(&self.0)
+    }
+
+    // one row per pair of straight lines, parallel pairs included
+    #[must_use]
+    pub fn intersection_rows(&self) -> Vec<IntersectionRow> {
+        let intersections = grid_intersections(&self.0);
+        let linear = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.fit_kind() == CurveFitKind::Linear)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        let mut rows = Vec::new();
+        for (n, &i) in linear.iter().enumerate() {
+            for &j in &linear[n + 1..] {
+                let world_pos = intersections
+                    .iter()
+                    .find(|(a, b, _)| (*a, *b) == (i, j))
+                    .map(|(_, _, p)| *p);
+                rows.push(IntersectionRow {
+                    line_a: self.0[i].name.clone(),
+                    line_b: self.0[j].name.clone(),
+                    world_pos,
+                });
+            }
+        }
+        rows
+    }
+
+    // `<name>.csv` or `<name>.json`, depending on the export format
+    #[must_use]
+    pub fn export_path(&self, idx: usize, save_dir: &Path, format: ExportFormat) -> PathBuf {
+        save_dir.join(format!("{}.{}", self.0[idx].name, format.extension()))
+    }
+
+    #[must_use]
+    pub fn export_request(
+        &self,
+        idx: usize,
+        save_dir: &Path,
+        csv_format: CsvFormat,
+        settings: ExportSettings,
+    ) -> IoRequest {
+        let line = &self.0[idx];
+        let (points, world_points) = if settings.resample {
+            let world_points = line.resample_to_n_points(settings.resample_points);
+            let transform = line.transform();
+            let points = world_points
+                .iter()
+                .map(|p| transform.apply_inverse(*p))
+                .collect();
+            (points, world_points)
+        } else {
+            (line.raw_point_coords(), line.raw_point_coords_world())
+        };
+        IoRequest::SaveLine {
+            line_id: line.id(),
+            points,
+            world_points,
+            transform: line.transform(),
+            csv_format,
+            file_path: self.export_path(idx, save_dir, settings.format),
+            settings,
+        }
+    }
+
+    // one request writing every line to `lines.<ext>` in the format of `settings`
+    #[must_use]
+    pub fn export_all_in_one(
+        &self,
+        save_dir: &Path,
+        transform: &PointTransform,
+        settings: ExportSettings,
+    ) -> IoRequest {
+        IoRequest::SaveAllLines {
+            lines: self.0.clone(),
+            transform: *transform,
+            file_path: save_dir.join(format!("lines.{}", settings.format.extension())),
+            settings,
+        }
+    }
+
+    #[must_use]
+    pub fn export_all(
+        &self,
+        save_dir: &Path,
+        csv_format: CsvFormat,
+        settings: ExportSettings,
+    ) -> Vec<IoRequest> {
+        (0..self.0.len())
+            .map(|idx| self.export_request(idx, save_dir, csv_format, settings))
+            .collect()
+    }
+}
+
+impl Index<usize> for LineSet {
+    type Output = ScreenLineSegment;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.0[idx]
+    }
+}
+
+impl IndexMut<usize> for LineSet {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        &mut self.0[idx]
+    }
+}
@@ -1,198 +1,2634 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
 use bounded_vec_deque::BoundedVecDeque;
+use clap::Parser;
 use eframe::egui;
-use egui::{ColorImage, InputState};
+use egui::{emath::TSTransform, ColorImage, InputState};
+use egui_plot::{Line, Plot, PlotPoints, Points};
 
-use point_handling::{
-    PointCoords, PointCoordsStringy, PointTransform, ScreenLineSegment, Transformable,
-    UniquePointBuf,
+use sci_shot::axis_detection::{detect_axis_scale, Axis};
+use sci_shot::error::SciShotError;
+use sci_shot::export::{self, CsvFormat, ExportFormat, ExportSettings, IoRequest, IoResponse};
+use sci_shot::io_utils::ensure_writable_dir;
+use sci_shot::line_set::LineSet;
+
+use sci_shot::point_handling::{
+    clip_segment, kurtosis, merge_nearby_points, point_in_polygon, remove_nearest, sample_formula,
+    skewness, smooth_moving_average, AxisTick, CurveFitKind, EndpointStyle, FitSpace,
+    LineOrientation, MeasurementKind, MeasurementResult, PointCoords, PointCoordsStringy,
+    PointStats, PointTransform, RGBColor, RegressionMode, ScreenLineSegment, SourceKind, ToCsvRow,
+    Transformable, UniquePointBuf,
 };
 
+use sci_shot::undo::{UndoAction, UndoStack};
+use world_label::{draw_world_labels, WorldSpaceLabel};
 use xcap::Monitor;
 
 static SCREENSHOT_TEXTURE: &str = "screenshot";
-static LINE_THICKNESS: f32 = 3.0;
-static POINT_RADIUS: f32 = 2.5;
-static NUM_CALIBRATION_POINTS: usize = 2;
+static WINDOW_TITLE: &str = "Sci-shot";
+static TRANSIENT_STATUS: Duration = Duration::from_secs(2);
+// offered by "Load image…", anything else the image crate reads can be picked as well
+static IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "gif", "tif", "tiff", "webp"];
+// ticks needed on each axis for the axis tick calibration
+static NUM_TICKS_PER_AXIS: usize = 2;
+static NEAREST_POINT_TOLERANCE: f32 = 10.0;
+// clicks closer than this (in pixels) are merged by "Deduplicate"
+static DUPLICATE_POINT_TOLERANCE: f32 = 2.0;
+// radii offered for "Cluster nearby", for sketches clicked over several times
+static CLUSTER_RADIUS: std::ops::RangeInclusive<f32> = 1.0..=50.0;
+static CURVE_SAMPLES: usize = 64;
+// offered for the bands, the ones `t_critical` has quantiles for
+static CONFIDENCE_LEVELS: [f32; 3] = [0.90, 0.95, 0.99];
+// lines shorter than this on screen are fitted from too few pixels to be trusted
+static MIN_SCREEN_LINE_LENGTH: f32 = 20.0;
+// residuals within these bounds are called normally distributed, a common rule of thumb
+static NORMAL_SKEWNESS: f32 = 0.5;
+static NORMAL_EXCESS_KURTOSIS: f32 = 1.0;
+static COLOR_SAMPLE_RADIUS: usize = 2;
+// windows that Tab cycles through, by title
+static FOCUS_CYCLE_WINDOWS: [&str; 3] =
+    ["Buffered points", "Line equations", "Transform calibration"];
+// the duplicate session shows the screenshot at this fraction of its size
+static DUPLICATE_SESSION_SCALE: f32 = 0.5;
+// line candidates tried by R, and how far (in pixels) a point may be from one to count
+static RANSAC_ITERATIONS: usize = 200;
+static RANSAC_INLIER_THRESHOLD: f32 = 5.0;
+static SMOOTHING_WINDOW: std::ops::RangeInclusive<usize> = 2..=15;
+// higher degrees mostly fit the clicking noise
+static POLYNOMIAL_DEGREE: std::ops::RangeInclusive<usize> = 2..=6;
+static REPLAY_INTERVAL_MS: std::ops::RangeInclusive<u64> = 50..=2000;
+static LIVE_CAPTURE_INTERVAL_MS: std::ops::RangeInclusive<u64> = 500..=5000;
+static ZOOM: std::ops::RangeInclusive<f32> = 0.25..=16.0;
+// zoom factor per scrolled point, so a notch of the wheel is roughly 10%
+static ZOOM_PER_SCROLL: f32 = 0.002;
+// the screenshot and everything drawn on it, so it can be zoomed without the panels
+static SCREENSHOT_LAYER: &str = "screenshot_layer";
+
+mod report;
+#[cfg(feature = "serde")]
+mod session;
+mod world_label;
 
-mod point_handling;
-enum PointGatheringState {
+enum AppState {
     Normal,
     Measurement,
+    RoiSelection(Option<egui::Rect>),
+    // clicks place the vertices, clicking the first one again closes the polygon
+    PolygonRoiSelection(Vec<PointCoords>),
+    DistanceMeasurement([Option<PointCoords>; 2]),
+    AngleMeasurement([Option<PointCoords>; 2]),
+    Delete,
+    Snap,
+    ColorPick,
+    TickCalibration,
+    // right clicks collect points that L adds to the line at this index
+    Extending(usize),
+    // choosing the screen to capture, at startup when there is more than one
+    MonitorSelect,
+    // left drag selects the part of the screen to capture, from this corner
+    CropSelection(Option<egui::Pos2>),
+}
+
+// Startup options, read from `config.toml` in the user's config directory. Missing keys keep
+// their defaults.
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct Settings {
+    // where exports go until changed in the export settings
+    save_dir: PathBuf,
+    line_thickness: f32,
+    point_radius: f32,
+    // screen points measured for the calibration at startup, at least 2. The calibration
+    // window can add more, the transform is then a least-squares fit
+    calibration_points: usize,
+    // decimals of the coordinates shown in the calibration window
+    calibration_precision: usize,
+    // number of states each undo history keeps, including the current one
+    undo_capacity: usize,
+    // how long the lines take to move to a new transform, 0 jumps straight there
+    transform_animation_secs: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            save_dir: std::env::current_dir()
+                .unwrap_or_default()
+                .join("exported_lines"),
+            line_thickness: 3.0,
+            point_radius: 2.5,
+            calibration_points: 2,
+            calibration_precision: 2,
+            undo_capacity: 64,
+            transform_animation_secs: 0.3,
+        }
+    }
+}
+
+impl Settings {
+    // `%APPDATA%\sci-shot\config.toml` on Windows, `~/.config/sci-shot/config.toml` elsewhere
+    fn path() -> Option<PathBuf> {
+        let config_dir = if cfg!(windows) {
+            PathBuf::from(std::env::var_os("APPDATA")?)
+        } else {
+            match std::env::var_os("XDG_CONFIG_HOME") {
+                Some(dir) => PathBuf::from(dir),
+                None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+            }
+        };
+        Some(config_dir.join("sci-shot").join("config.toml"))
+    }
+
+    // the defaults when there is no config file, with a warning when it can't be used
+    fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Settings::default();
+        };
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Settings::default(),
+            Err(e) => {
+                eprintln!("Ignoring {}: {}", path.display(), e);
+                return Settings::default();
+            }
+        };
+        match toml::from_str::<Settings>(&contents) {
+            Ok(settings) => Settings {
+                calibration_points: settings.calibration_points.max(2),
+                ..settings
+            },
+            Err(e) => {
+                eprintln!("Ignoring malformed {}: {}", path.display(), e);
+                Settings::default()
+            }
+        }
+    }
+}
+
+// What a line's cached results were computed from, they are stale once any of it differs.
+// The fitted slope and intercept pick up the regression mode and fit space.
+#[derive(Clone, PartialEq)]
+struct FitFingerprint {
+    points: UniquePointBuf,
+    transform: PointTransform,
+    fit_kind: CurveFitKind,
+    slope: f32,
+    intercept: f32,
+}
+
+impl FitFingerprint {
+    fn of(line: &ScreenLineSegment) -> Self {
+        FitFingerprint {
+            points: line.screen_points().clone(),
+            transform: line.transform(),
+            fit_kind: line.fit_kind(),
+            slope: line.world_slope(),
+            intercept: line.world_intercept(),
+        }
+    }
+}
+
+// finished influence job: line id, what it was computed from and each point's influence
+type InfluenceResult = (u64, FitFingerprint, Vec<(PointCoords, f32)>);
+
+// painted band: what it was computed from, its confidence level and (lower, upper) points
+type CachedBand = (FitFingerprint, Option<f32>, Vec<(PointCoords, PointCoords)>);
+
+// one state of the point and line history, with the change that led to it
+#[derive(Clone, Default)]
+struct HistoryEntry {
+    points: UniquePointBuf,
+    lines: LineSet,
+    // None only for the empty state the history starts from
+    action: Option<UndoAction>,
+}
+
+// where the screenshot comes from
+enum ScreenshotSource {
+    LiveCapture(Monitor),
+    // an image of a paper or a simulation that isn't on screen
+    File(PathBuf),
+    // pasted with Ctrl+V, e.g. from another screenshot tool
+    Clipboard,
+}
+
+fn crop_image(image: image::RgbaImage, rect: egui::Rect) -> image::RgbaImage {
+    let bounds = egui::Rect::from_min_size(
+        egui::Pos2::ZERO,
+        egui::vec2(image.width() as f32, image.height() as f32),
+    );
+    let rect = rect.intersect(bounds);
+    if rect.width() < 1.0 || rect.height() < 1.0 {
+        return image;
+    }
+    image::imageops::crop_imm(
+        &image,
+        rect.min.x as u32,
+        rect.min.y as u32,
+        rect.width() as u32,
+        rect.height() as u32,
+    )
+    .to_image()
+}
+
+impl ScreenshotSource {
+    // `crop` is in pixels of the monitor or file, it is ignored when it misses the image
+    fn capture(&self, crop: Option<egui::Rect>) -> Result<ColorImage, SciShotError> {
+        let screenshot = match self {
+            ScreenshotSource::LiveCapture(monitor) => monitor
+                .capture_image()
+                .map_err(|e| SciShotError::IoError(io::Error::other(e)))?,
+            ScreenshotSource::File(path) => image::open(path)
+                .map_err(|e| SciShotError::ParseError(format!("{}: {}", path.display(), e)))?
+                .into_rgba8(),
+            ScreenshotSource::Clipboard => {
+                let image = arboard::Clipboard::new()
+                    .and_then(|mut clipboard| clipboard.get_image())
+                    .map_err(|e| SciShotError::IoError(io::Error::other(e)))?;
+                // arboard hands out tightly packed RGBA rows, so the length always matches
+                image::RgbaImage::from_raw(
+                    image.width as u32,
+                    image.height as u32,
+                    image.bytes.into_owned(),
+                )
+                .unwrap()
+            }
+        };
+        let screenshot = match crop {
+            Some(rect) => crop_image(screenshot, rect),
+            None => screenshot,
+        };
+        let pixels = screenshot.as_flat_samples();
+        let size = [screenshot.width() as _, screenshot.height() as _]; // needed to match usize
+        Ok(ColorImage::from_rgba_unmultiplied(size, pixels.as_slice()))
+    }
+
+    // monitor name or file path, for the title bar and the report
+    fn description(&self) -> String {
+        match self {
+            ScreenshotSource::LiveCapture(monitor) => format!("monitor {}", monitor.name()),
+            ScreenshotSource::File(path) => path.display().to_string(),
+            ScreenshotSource::Clipboard => "clipboard".to_owned(),
+        }
+    }
+}
+
+// a tick placed with a hotkey whose value is still being typed in
+struct PendingTick {
+    axis: Axis,
+    screen_pos: PointCoords,
+    value: String,
+}
+
+impl AppState {
+    fn display_name(&self) -> &str {
+        match self {
+            AppState::Normal => "Normal",
+            AppState::Measurement => "Calibration",
+            AppState::RoiSelection(_) => "ROI selection",
+            AppState::PolygonRoiSelection(_) => "Polygon ROI",
+            AppState::DistanceMeasurement(_) => "Distance",
+            AppState::AngleMeasurement(_) => "Angle",
+            AppState::Delete => "Delete",
+            AppState::Snap => "Snap to line",
+            AppState::ColorPick => "Colour pick",
+            AppState::TickCalibration => "Axis ticks",
+            AppState::Extending(_) => "Extending line",
+            AppState::MonitorSelect => "Monitor selection",
+            AppState::CropSelection(_) => "Crop capture",
+        }
+    }
+
+    fn is_same_mode(&self, other: &AppState) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 }
 
 struct App {
-    preferred_monitor: Monitor,
+    screenshot_source: ScreenshotSource,
+    // listed by the monitor selection, refreshed whenever it is opened
+    monitors: Vec<Monitor>,
     screenshot_texture_handle: Option<egui::TextureHandle>,
-    gathering_state: PointGatheringState,
+    // CPU-side copy of the texture, for sampling pixel colours
+    screenshot_image: Option<ColorImage>,
+    target_color: Option<egui::Color32>,
+    color_filter_enabled: bool,
+    color_tolerance: f32,
+    live_capture: bool,
+    live_capture_interval_ms: u64,
+    last_capture: Instant,
+    captured_at: SystemTime,
+    state: AppState,
+    roi: Option<egui::Rect>,
+    // points must also be inside this, for plots that aren't rectangular
+    roi_polygon: Option<Vec<PointCoords>>,
+    // part of the monitor (or image file) that is captured, in its pixels, None for all of
+    // it. Only changed through `set_crop`, which keeps the rest of the screen-space state
+    // over the same image content.
+    crop_rect: Option<egui::Rect>,
     buffered_points: UniquePointBuf,
+    smoothing_window: usize,
+    cluster_radius: f32,
+    // number of buffered points shown while the placement is being replayed
+    replay_step: Option<usize>,
+    replay_interval_ms: u64,
+    // show buffered points as (r, θ), purely cosmetic
+    polar_display: bool,
+    // view of the screenshot, points are always stored in screenshot pixels
+    zoom: f32,
+    pan_offset: egui::Vec2,
+    // zoom and pan ignore the mouse, e.g. while presenting
+    viewport_locked: bool,
+    last_replay_step: Instant,
     measurement_buffer: BoundedVecDeque<PointCoords>,
     measurement_buffer_real_world: BoundedVecDeque<PointCoords>,
     measurement_buffer_rw_s: BoundedVecDeque<PointCoordsStringy>,
-    regression_lines: Vec<ScreenLineSegment>,
+    // the (screen, world) pairs behind the last point calibration and the transform they
+    // gave, saved with the calibration only while that transform is still the current one
+    calibration_pairs: Option<(PointTransform, Vec<(PointCoords, PointCoords)>)>,
+    horizontal_ticks: BoundedVecDeque<AxisTick>,
+    vertical_ticks: BoundedVecDeque<AxisTick>,
+    pending_tick: Option<PendingTick>,
+    // second detected tick, asked for once `pending_tick` has its value
+    queued_tick: Option<PendingTick>,
+    regression_lines: LineSet,
+    // line picked in the "Line equations" window, S exports only this one
+    selected_line_idx: Option<usize>,
+    // lines ticked for the bulk delete/export buttons
+    checked_lines: HashSet<usize>,
+    // opacity of the standard deviation bands
+    band_alpha: f32,
+    // the bands show this confidence level of the fit, or ±1 standard deviation when None
+    confidence_level: Option<f32>,
+    // by line id, the band last painted and the fit and confidence level it belongs to, the
+    // bootstrap behind non-linear bands is too slow to rerun every frame
+    bands: HashMap<u64, CachedBand>,
+    // the world-space plot shows residuals instead of the data
+    plot_residuals: bool,
+    // column of the "Intersections" table the rows are sorted by
+    intersection_sort_column: usize,
+    intersection_sort_ascending: bool,
+    // the intersections are also labelled on the screenshot
+    show_intersection_labels: bool,
+    // formula in x drawn on top of the screenshot for comparison, not fitted
+    theory_formula: String,
+    theory_enabled: bool,
+    // points and lines share one history so Ctrl+Z steps back through both in order
+    history: UndoStack<HistoryEntry>,
+    // deep copy of the lines that can be edited separately for comparison
+    duplicate_session: Option<LineSet>,
+    fit_kind: CurveFitKind,
+    // applies to every linear fit, including the existing lines
+    regression_mode: RegressionMode,
+    // applies to every linear fit as well
+    fit_space: FitSpace,
+    measurements: Vec<MeasurementResult>,
+    // what the lines are drawn with, eases towards `target_transform` when it changes
     current_transform: PointTransform,
+    // the calibrated transform, used for every value shown or measured
+    target_transform: PointTransform,
+    transform_animation_start: PointTransform,
+    // progress of the easing from `transform_animation_start` to `target_transform`
+    lerp_t: f32,
+    settings: Settings,
+    transform_history: UndoStack<PointTransform>,
+    // position of the blend slider between the two most recent calibrations
+    transform_blend: f32,
+    // sx, sy, ox, oy of the manual scale-and-offset calibration
+    manual_scale: [f32; 4],
+    io_sender: Sender<IoRequest>,
+    io_receiver: Receiver<IoResponse>,
+    io_in_flight: usize,
+    // lines with an export still being written, their export button is disabled
+    pending_line_exports: HashSet<u64>,
+    save_dir: PathBuf,
+    export_settings: ExportSettings,
+    // columns of the line CSVs
+    csv_format: CsvFormat,
+    show_export_settings: bool,
+    load_path: String,
+    status_message: String,
+    transient_status: Option<(String, Instant)>,
+    focused_window: usize,
+    // by line id, the influence of each point of the line and what it was computed from
+    influence: HashMap<u64, (FitFingerprint, HashMap<PointCoords, f32>)>,
+    influence_sender: Sender<InfluenceResult>,
+    influence_receiver: Receiver<InfluenceResult>,
+    // ids of the lines with a job running, at most one per line
+    influence_jobs: HashSet<u64>,
 }
 
+// alt+right click is left to `delete_click_pos`
 fn secondary_btn_click_pos(i: &InputState) -> Option<egui::Pos2> {
-    if i.pointer.secondary_clicked() {
+    if i.pointer.secondary_clicked() && !i.modifiers.alt {
         return i.pointer.latest_pos();
     }
     None
 }
 
+// middle click or alt+right click, both delete the nearest buffered point
+fn delete_click_pos(i: &InputState) -> Option<egui::Pos2> {
+    let middle = i.pointer.button_clicked(egui::PointerButton::Middle);
+    let alt_right = i.pointer.secondary_clicked() && i.modifiers.alt;
+    if middle || alt_right {
+        return i.pointer.latest_pos();
+    }
+    None
+}
+
+// whether `pos` is on the screenshot rather than a window or panel on top of it
+fn is_over_screenshot(ui: &egui::Ui, pos: egui::Pos2) -> bool {
+    ui.max_rect().contains(pos)
+        && ui
+            .ctx()
+            .layer_id_at(pos)
+            .map_or(true, |layer| layer.order == egui::Order::Background)
+}
+
+// fills between the (lower, upper) pairs of a band, one quad per step along the curve
+fn paint_confidence_band(
+    painter: &egui::Painter,
+    band: &[(PointCoords, PointCoords)],
+    fill: egui::Color32,
+) {
+    for pair in band.windows(2) {
+        let ((lower_a, upper_a), (lower_b, upper_b)) = (pair[0], pair[1]);
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                lower_a.into(),
+                lower_b.into(),
+                upper_b.into(),
+                upper_a.into(),
+            ],
+            fill,
+            egui::Stroke::NONE,
+        ));
+    }
+}
+
+// wall clock time of day, there is no need for a date library just for the overlay
+fn format_time_of_day(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) % 86400;
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60
+    )
+}
+
+// compares hue (circularly), saturation and value, each within `tolerance` in [0, 1]
+fn colors_match(a: egui::Color32, b: egui::Color32, tolerance: f32) -> bool {
+    let a = egui::ecolor::Hsva::from(a);
+    let b = egui::ecolor::Hsva::from(b);
+    let hue_diff = (a.h - b.h).abs();
+    hue_diff.min(1.0 - hue_diff) <= tolerance
+        && (a.s - b.s).abs() <= tolerance
+        && (a.v - b.v).abs() <= tolerance
+}
+
+// single line text edit that turns red while its content isn't a number
+fn numeric_text_edit(text: &mut String) -> egui::TextEdit<'_> {
+    let is_numeric = text.parse::<f32>().is_ok();
+    egui::TextEdit::singleline(text).text_color_opt((!is_numeric).then_some(egui::Color32::RED))
+}
+
+// decorates the ends of a drawn line, the unit vector `direction` orients the arrow
+// head at `end`
+fn paint_endpoints(
+    painter: &egui::Painter,
+    style: EndpointStyle,
+    [start, end]: [egui::Pos2; 2],
+    direction: egui::Vec2,
+    stroke: egui::Stroke,
+) {
+    let size = stroke.width * 2.0;
+    match style {
+        EndpointStyle::None => {}
+        EndpointStyle::Circle => {
+            painter.circle_filled(start, size, stroke.color);
+            painter.circle_filled(end, size, stroke.color);
+        }
+        EndpointStyle::Square => {
+            for pos in [start, end] {
+                painter.rect_filled(
+                    egui::Rect::from_center_size(pos, egui::Vec2::splat(size * 2.0)),
+                    0.0,
+                    stroke.color,
+                );
+            }
+        }
+        EndpointStyle::Arrow => {
+            let back = -direction * size * 2.0;
+            let side = back.rot90() * 0.5;
+            painter.add(egui::Shape::convex_polygon(
+                vec![end, end + back + side, end + back - side],
+                stroke.color,
+                egui::Stroke::NONE,
+            ));
+        }
+    }
+}
+
+// the clipboard text as a path, if it names an existing image file
+fn clipboard_image_path() -> Option<PathBuf> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()?;
+    let text = text.trim().trim_matches('"');
+    let path = PathBuf::from(text.strip_prefix("file://").unwrap_or(text));
+    let is_image = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+    (is_image && path.is_file()).then_some(path)
+}
+
+// single-key shortcuts must not fire while the user is typing into a text field
+fn hotkey_pressed(ctx: &egui::Context, key: egui::Key) -> bool {
+    !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(key))
+}
+
+// Ctrl+V, on release: egui-winit turns the press into a text paste event, which never
+// comes when the clipboard holds an image
+fn paste_released(i: &InputState) -> bool {
+    i.events.iter().any(|event| {
+        matches!(
+            event,
+            egui::Event::Key {
+                key: egui::Key::V,
+                pressed: false,
+                modifiers,
+                ..
+            } if modifiers.command
+        )
+    })
+}
+
+// like `hotkey_pressed`, but for key combinations; text fields keep their own undo
+fn shortcut_pressed(ctx: &egui::Context, modifiers: egui::Modifiers, key: egui::Key) -> bool {
+    !ctx.wants_keyboard_input() && ctx.input_mut(|i| i.consume_key(modifiers, key))
+}
+
 impl Default for App {
     fn default() -> Self {
-        let primary = Monitor::all()
-            .unwrap()
-            .into_iter()
-            .find(|m| m.is_primary())
-            .unwrap();
+        let monitors = Monitor::all().unwrap();
+        let primary = monitors.iter().find(|m| m.is_primary()).unwrap().clone();
+        let (io_sender, io_receiver) = export::spawn_io_thread();
+        let (influence_sender, influence_receiver) = mpsc::channel();
+        let settings = Settings::load();
         App {
-            preferred_monitor: primary,
+            history: UndoStack::new(HistoryEntry::default(), settings.undo_capacity),
+            transform_history: UndoStack::new(PointTransform::identity(), settings.undo_capacity),
+            screenshot_source: ScreenshotSource::LiveCapture(primary),
+            state: if monitors.len() > 1 {
+                AppState::MonitorSelect
+            } else {
+                AppState::Normal
+            },
+            monitors,
             screenshot_texture_handle: None,
-            gathering_state: PointGatheringState::Normal,
+            screenshot_image: None,
+            target_color: None,
+            color_filter_enabled: false,
+            color_tolerance: 0.1,
+            live_capture: false,
+            live_capture_interval_ms: 1000,
+            last_capture: Instant::now(),
+            captured_at: SystemTime::now(),
+            roi: None,
+            roi_polygon: None,
+            crop_rect: None,
             buffered_points: UniquePointBuf::new(),
-            measurement_buffer: BoundedVecDeque::new(NUM_CALIBRATION_POINTS),
+            smoothing_window: 3,
+            cluster_radius: 5.0,
+            replay_step: None,
+            replay_interval_ms: 300,
+            polar_display: false,
+            zoom: 1.0,
+            pan_offset: egui::Vec2::ZERO,
+            viewport_locked: false,
+            last_replay_step: Instant::now(),
+            measurement_buffer: BoundedVecDeque::new(settings.calibration_points),
             measurement_buffer_real_world: BoundedVecDeque::from_iter(
                 std::iter::repeat(PointCoords::new(0.0, 0.0)),
-                NUM_CALIBRATION_POINTS,
+                settings.calibration_points,
             ),
             measurement_buffer_rw_s: BoundedVecDeque::from_iter(
-                std::iter::repeat(PointCoordsStringy::new_numeric(0.0, 0.0)),
-                NUM_CALIBRATION_POINTS,
+                std::iter::repeat(PointCoordsStringy::new_formatted(
+                    0.0,
+                    0.0,
+                    settings.calibration_precision,
+                )),
+                settings.calibration_points,
             ),
-            regression_lines: Vec::new(),
-            current_transform: PointTransform {
-                alpha: 1.0,
-                beta: 0.0,
-                dx: 0.0,
-                dy: 0.0,
-            },
+            calibration_pairs: None,
+            horizontal_ticks: BoundedVecDeque::new(NUM_TICKS_PER_AXIS),
+            vertical_ticks: BoundedVecDeque::new(NUM_TICKS_PER_AXIS),
+            pending_tick: None,
+            queued_tick: None,
+            regression_lines: LineSet::default(),
+            selected_line_idx: None,
+            checked_lines: HashSet::new(),
+            band_alpha: 0.25,
+            confidence_level: None,
+            bands: HashMap::new(),
+            plot_residuals: false,
+            intersection_sort_column: 0,
+            intersection_sort_ascending: true,
+            show_intersection_labels: false,
+            theory_formula: String::new(),
+            theory_enabled: false,
+            duplicate_session: None,
+            fit_kind: CurveFitKind::Linear,
+            regression_mode: RegressionMode::Ols,
+            fit_space: FitSpace::World,
+            measurements: Vec::new(),
+            current_transform: PointTransform::identity(),
+            target_transform: PointTransform::identity(),
+            transform_animation_start: PointTransform::identity(),
+            lerp_t: 1.0,
+            transform_blend: 1.0,
+            manual_scale: [1.0, -1.0, 0.0, 0.0],
+            io_sender,
+            io_receiver,
+            io_in_flight: 0,
+            pending_line_exports: HashSet::new(),
+            save_dir: settings.save_dir.clone(),
+            export_settings: ExportSettings::default(),
+            csv_format: CsvFormat::default(),
+            show_export_settings: false,
+            load_path: String::new(),
+            status_message: String::new(),
+            transient_status: None,
+            focused_window: 0,
+            influence: HashMap::new(),
+            influence_sender,
+            influence_receiver,
+            influence_jobs: HashSet::new(),
+            settings,
+        }
+    }
+}
+
+impl App {
+    // Makes `source` the screenshot, cropped to `crop`, unless it can't be read. Returns
+    // whether it could. The previous crop belongs to the previous image and is dropped.
+    fn set_screenshot_source(
+        &mut self,
+        ctx: &egui::Context,
+        source: ScreenshotSource,
+        crop: Option<egui::Rect>,
+    ) -> bool {
+        let image = match source.capture(crop) {
+            Ok(image) => image,
+            Err(e) => {
+                self.status_message = e.to_string();
+                return false;
+            }
+        };
+        self.show_screenshot(ctx, source, image, crop);
+        true
+    }
+
+    // `image` is what `source` captured with `crop`
+    fn show_screenshot(
+        &mut self,
+        ctx: &egui::Context,
+        source: ScreenshotSource,
+        image: ColorImage,
+        crop: Option<egui::Rect>,
+    ) {
+        self.upload_screenshot(ctx, image);
+        self.screenshot_source = source;
+        self.crop_rect = crop;
+        self.update_window_title(ctx);
+    }
+
+    // the title shows where the screenshot came from, unless it is a monitor
+    fn update_window_title(&self, ctx: &egui::Context) {
+        let title = match &self.screenshot_source {
+            ScreenshotSource::LiveCapture(_) => WINDOW_TITLE.to_owned(),
+            source => format!("{} - {}", WINDOW_TITLE, source.description()),
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+    }
+
+    // Ctrl+V: an image in the clipboard becomes the screenshot, copied text is tried as the
+    // path of an image file, e.g. a file copied in a file manager
+    fn import_screenshot_from_clipboard(&mut self, ctx: &egui::Context) {
+        if let Some(image) = self.try_screenshot_from_clipboard() {
+            self.show_screenshot(ctx, ScreenshotSource::Clipboard, image, None);
+        } else if let Some(path) = clipboard_image_path() {
+            if !self.set_screenshot_source(ctx, ScreenshotSource::File(path), None) {
+                return;
+            }
+        } else {
+            self.show_transient_status("No image in clipboard");
+            return;
+        }
+        if let Some(image) = &self.screenshot_image {
+            let [width, height] = image.size;
+            self.status_message = format!("Pasted image ({}×{} px)", width, height);
+        }
+    }
+
+    // the clipboard image, uncropped, or None when it holds none
+    fn try_screenshot_from_clipboard(&self) -> Option<ColorImage> {
+        ScreenshotSource::Clipboard.capture(None).ok()
+    }
+
+    // shown instead of the status message until it expires
+    fn show_transient_status(&mut self, message: &str) {
+        self.transient_status = Some((message.to_owned(), Instant::now() + TRANSIENT_STATUS));
+    }
+
+    // captures the current source again, e.g. after the crop changed
+    fn recapture(&mut self, ctx: &egui::Context) {
+        match self.screenshot_source.capture(self.crop_rect) {
+            Ok(image) => self.upload_screenshot(ctx, image),
+            Err(e) => self.status_message = e.to_string(),
+        }
+    }
+
+    fn upload_screenshot(&mut self, ctx: &egui::Context, image: ColorImage) {
+        self.screenshot_image = Some(image.clone());
+        match self.screenshot_texture_handle.as_mut() {
+            Some(handle) => handle.set(image, Default::default()),
+            None => {
+                self.screenshot_texture_handle =
+                    Some(ctx.load_texture(SCREENSHOT_TEXTURE, image, Default::default()));
+            }
+        }
+        self.last_capture = Instant::now();
+        self.captured_at = SystemTime::now();
+    }
+
+    // left drag in crop selection mode, the capture is cropped to the rectangle on release
+    fn update_crop_selection(&mut self, ui: &egui::Ui) {
+        let AppState::CropSelection(start) = self.state else {
+            return;
+        };
+        let (pressed, released, pos) = ui.input(|i| {
+            (
+                i.pointer.primary_pressed(),
+                i.pointer.primary_released(),
+                i.pointer.interact_pos(),
+            )
+        });
+        let Some(pos) = pos else {
+            return;
+        };
+        match start {
+            None if pressed && is_over_screenshot(ui, pos) => {
+                self.state = AppState::CropSelection(Some(self.screenshot_pos(pos)));
+            }
+            Some(start) if released => {
+                let selection = egui::Rect::from_two_pos(start, self.screenshot_pos(pos));
+                self.state = AppState::Normal;
+                if selection.width() < 1.0 || selection.height() < 1.0 {
+                    return;
+                }
+                // the screenshot may already be cropped, the new crop is within it
+                let offset = self
+                    .crop_rect
+                    .map_or(egui::Vec2::ZERO, |crop| crop.min.to_vec2());
+                self.set_crop(ui.ctx(), Some(selection.translate(offset)));
+            }
+            Some(start) => {
+                ui.painter().rect(
+                    egui::Rect::from_two_pos(start, self.screenshot_pos(pos)),
+                    0.0,
+                    egui::Color32::from_black_alpha(96),
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                );
+            }
+            None => {}
+        }
+    }
+
+    // Recaptures with a new crop. Lines and buffered points are refused rather than moved,
+    // their fits, caches and undo history are all in the old pixel frame. The ROI, the
+    // calibration points and ticks, and the transform follow the image content.
+    fn set_crop(&mut self, ctx: &egui::Context, crop: Option<egui::Rect>) {
+        if !self.buffered_points.is_empty() || !self.regression_lines.is_empty() {
+            self.show_transient_status("Remove the points and lines before changing the crop");
+            return;
+        }
+        let origin = |crop: Option<egui::Rect>| crop.map_or(egui::Vec2::ZERO, |c| c.min.to_vec2());
+        let delta = origin(crop) - origin(self.crop_rect);
+        self.crop_rect = crop;
+        self.shift_screen_state(delta);
+        self.recapture(ctx);
+    }
+
+    // moves what is stored in screenshot pixels by -`delta`, for an image origin that moved
+    // by `delta`
+    fn shift_screen_state(&mut self, delta: egui::Vec2) {
+        let shift = |p: &mut PointCoords| {
+            *p = PointCoords::new(p.x.into_inner() - delta.x, p.y.into_inner() - delta.y);
+        };
+        self.roi = self.roi.map(|roi| roi.translate(-delta));
+        self.roi_polygon
+            .iter_mut()
+            .flatten()
+            .chain(self.measurement_buffer.iter_mut())
+            .for_each(shift);
+        self.horizontal_ticks
+            .iter_mut()
+            .chain(self.vertical_ticks.iter_mut())
+            .for_each(|tick| shift(&mut tick.screen_pos));
+        self.pending_tick
+            .iter_mut()
+            .chain(self.queued_tick.iter_mut())
+            .for_each(|tick| shift(&mut tick.screen_pos));
+        // the new pixel p was p + delta before, so the world origin of the screen is T(delta)
+        let moved = |t: &mut PointTransform| {
+            let origin = PointCoords::new(delta.x, delta.y).transform(t);
+            *t = PointTransform::new(
+                t.alpha,
+                t.beta,
+                origin.x.into_inner(),
+                origin.y.into_inner(),
+            );
+        };
+        moved(&mut self.current_transform);
+        moved(&mut self.target_transform);
+        moved(&mut self.transform_animation_start);
+        if let Some((transform, pairs)) = &mut self.calibration_pairs {
+            moved(transform);
+            pairs.iter_mut().for_each(|(screen, _)| shift(screen));
+        }
+        self.transform_history.for_each_mut(moved);
+    }
+
+    fn pick_image_file(&mut self, ctx: &egui::Context) {
+        let picked = rfd::FileDialog::new()
+            .add_filter("Images", IMAGE_EXTENSIONS)
+            .pick_file();
+        if let Some(path) = picked {
+            self.set_screenshot_source(ctx, ScreenshotSource::File(path), None);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn pick_session_to_save(&mut self) {
+        let picked = rfd::FileDialog::new()
+            .add_filter("Sci-shot session", &["json"])
+            .set_directory(&self.save_dir)
+            .set_file_name("session.json")
+            .save_file();
+        if let Some(path) = picked {
+            self.status_message = match self.save_session(&path) {
+                Ok(()) => format!("Saved session to {}", path.display()),
+                Err(e) => format!("Failed to save session: {}", e),
+            };
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn pick_session_to_load(&mut self, ctx: &egui::Context) {
+        let picked = rfd::FileDialog::new()
+            .add_filter("Sci-shot session", &["json"])
+            .set_directory(&self.save_dir)
+            .pick_file();
+        if let Some(path) = picked {
+            self.status_message = match self.load_session(ctx, &path) {
+                Ok(()) => format!("Loaded session from {}", path.display()),
+                Err(e) => format!("Failed to load session: {}", e),
+            };
+        }
+    }
+
+    fn pick_calibration_to_save(&mut self) {
+        let picked = rfd::FileDialog::new()
+            .add_filter("Calibration", &["json"])
+            .set_directory(&self.save_dir)
+            .set_file_name("calibration.json")
+            .save_file();
+        if let Some(path) = picked {
+            // pairs from an older calibration would refit to a different transform on load
+            let pairs = match &self.calibration_pairs {
+                Some((transform, pairs)) if *transform == self.target_transform => pairs.as_slice(),
+                _ => &[],
+            };
+            let result = self.target_transform.save_to_file(&path, pairs);
+            self.status_message = match result {
+                Ok(()) => format!("Saved calibration to {}", path.display()),
+                Err(e) => format!("Failed to save calibration: {}", e),
+            };
+        }
+    }
+
+    fn pick_calibration_to_load(&mut self) {
+        let picked = rfd::FileDialog::new()
+            .add_filter("Calibration", &["json"])
+            .set_directory(&self.save_dir)
+            .pick_file();
+        if let Some(path) = picked {
+            self.status_message = match PointTransform::load_from_file(&path) {
+                Ok((transform, pairs)) => {
+                    self.fill_calibration_rows(&pairs);
+                    self.apply_calibration(transform);
+                    self.calibration_pairs = Some((transform, pairs));
+                    format!("Loaded calibration from {}", path.display())
+                }
+                Err(e) => format!("Failed to load calibration: {}", e),
+            };
+        }
+    }
+
+    // fills the calibration window with loaded pairs, keeping at least two rows
+    fn fill_calibration_rows(&mut self, pairs: &[(PointCoords, PointCoords)]) {
+        if pairs.is_empty() {
+            return;
+        }
+        let rows = pairs.len().max(2);
+        self.measurement_buffer = BoundedVecDeque::new(rows);
+        self.measurement_buffer_real_world = BoundedVecDeque::new(rows);
+        self.measurement_buffer_rw_s = BoundedVecDeque::new(rows);
+        let precision = self.settings.calibration_precision;
+        for &(screen, world) in pairs {
+            let _ = self.measurement_buffer.push_back(screen);
+            let _ = self.measurement_buffer_real_world.push_back(world);
+            let _ = self
+                .measurement_buffer_rw_s
+                .push_back(PointCoordsStringy::new_formatted(
+                    world.x.into_inner(),
+                    world.y.into_inner(),
+                    precision,
+                ));
+        }
+        while self.measurement_buffer_rw_s.len() < rows {
+            let _ = self
+                .measurement_buffer_real_world
+                .push_back(PointCoords::new(0.0, 0.0));
+            let _ = self
+                .measurement_buffer_rw_s
+                .push_back(PointCoordsStringy::new_formatted(0.0, 0.0, precision));
+        }
+    }
+
+    // maps screenshot pixels to where they are drawn
+    fn view_transform(&self) -> TSTransform {
+        TSTransform::new(self.pan_offset, self.zoom)
+    }
+
+    // screenshot pixel under a pointer position
+    fn screenshot_pos(&self, pos: egui::Pos2) -> egui::Pos2 {
+        self.view_transform().inverse() * pos
+    }
+
+    // middle drag pans, scrolling zooms around the cursor; only over the screenshot, the
+    // windows scroll their own content
+    fn update_view(&mut self, ui: &egui::Ui) {
+        if self.viewport_locked {
+            return;
+        }
+        let ctx = ui.ctx();
+        let Some(pos) = ctx.input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+        if !is_over_screenshot(ui, pos) {
+            return;
+        }
+        let (dragged, scrolled) = ctx.input(|i| {
+            let dragged = i.pointer.button_down(egui::PointerButton::Middle);
+            (
+                dragged.then_some(i.pointer.delta()),
+                i.smooth_scroll_delta.y,
+            )
+        });
+        if let Some(delta) = dragged {
+            self.pan_offset += delta;
+        }
+        if scrolled != 0.0 {
+            let zoom =
+                (self.zoom * (scrolled * ZOOM_PER_SCROLL).exp()).clamp(*ZOOM.start(), *ZOOM.end());
+            // keep the pixel under the cursor where it is
+            self.pan_offset =
+                pos.to_vec2() - (pos.to_vec2() - self.pan_offset) * (zoom / self.zoom);
+            self.zoom = zoom;
+        }
+    }
+
+    fn draw_screenshot_layer(&mut self, ui: &mut egui::Ui) {
+        if self.screenshot_texture_handle.is_none() {
+            let image = self.screenshot_source.capture(self.crop_rect).unwrap();
+            self.screenshot_image = Some(image.clone());
+            let handle = ui
+                .ctx()
+                .load_texture(SCREENSHOT_TEXTURE, image, Default::default());
+            self.screenshot_texture_handle = Some(handle);
+            self.last_capture = Instant::now();
+            self.captured_at = SystemTime::now();
+        }
+
+        if self.live_capture {
+            self.refresh_live_capture(ui.ctx());
+        }
+
+        // unwrap is safe because we just set it if it was None
+        let response = ui.image(egui::load::SizedTexture::from_handle(
+            self.screenshot_texture_handle.as_ref().unwrap(),
+        ));
+
+        if self.live_capture {
+            ui.painter().text(
+                response.rect.right_top() + egui::vec2(-10.0, 10.0),
+                egui::Align2::RIGHT_TOP,
+                format_time_of_day(self.captured_at),
+                egui::FontId::monospace(16.0),
+                egui::Color32::YELLOW,
+            );
+        }
+    }
+
+    // re-uploads the screenshot texture once the capture interval has elapsed
+    fn refresh_live_capture(&mut self, ctx: &egui::Context) {
+        let interval = Duration::from_millis(self.live_capture_interval_ms);
+        let elapsed = self.last_capture.elapsed();
+        if elapsed >= interval {
+            // a file that is being rewritten may not read, the last image stays up then
+            if let Ok(image) = self.screenshot_source.capture(self.crop_rect) {
+                self.screenshot_image = Some(image.clone());
+                if let Some(handle) = self.screenshot_texture_handle.as_mut() {
+                    handle.set(image, Default::default());
+                }
+            }
+            self.last_capture = Instant::now();
+            self.captured_at = SystemTime::now();
+            ctx.request_repaint_after(interval);
+        } else {
+            ctx.request_repaint_after(interval - elapsed);
+        }
+    }
+
+    // reveals one more buffered point, in placement order, every replay interval
+    fn advance_replay(&mut self, ctx: &egui::Context) {
+        let Some(step) = self.replay_step else {
+            return;
+        };
+        let interval = Duration::from_millis(self.replay_interval_ms);
+        let elapsed = self.last_replay_step.elapsed();
+        if elapsed < interval {
+            ctx.request_repaint_after(interval - elapsed);
+            return;
+        }
+        if step >= self.buffered_points.len() {
+            self.replay_step = None;
+            return;
+        }
+        self.replay_step = Some(step + 1);
+        self.last_replay_step = Instant::now();
+        ctx.request_repaint_after(interval);
+    }
+
+    // buffered points in red, points that RANSAC left out of a line in orange
+    fn paint_buffered_points(&mut self, ui: &egui::Ui) {
+        let outliers = self
+            .regression_lines
+            .iter_visible()
+            .flat_map(|line| line.outliers().iter())
+            .map(|p| (p, egui::Color32::from_rgb(255, 165, 0)));
+        let buffered = self.get_buffer_iterator().map(|p| (p, egui::Color32::RED));
+        for (point, fill) in outliers.chain(buffered) {
+            ui.painter()
+                .add(egui::Shape::Circle(egui::epaint::CircleShape {
+                    center: (*point).into(),
+                    radius: self.settings.point_radius,
+                    fill,
+                    stroke: Default::default(),
+                }));
+        }
+    }
+
+    // colours the points of lines with the influence view enabled from white (no
+    // influence on the slope) to red (the most influential point of that line)
+    fn paint_influence(&self, ui: &egui::Ui) {
+        for line in self
+            .regression_lines
+            .iter_visible()
+            .filter(|l| l.show_influence)
+        {
+            // stale values are left out until `refresh_influence` replaced them
+            let influence = match self.influence.get(&line.id()) {
+                Some((fingerprint, influence)) if *fingerprint == FitFingerprint::of(line) => {
+                    influence
+                }
+                _ => continue,
+            };
+            let points = line.raw_point_coords();
+            let max_influence = points
+                .iter()
+                .filter_map(|p| influence.get(p))
+                .fold(0.0_f32, |acc, v| acc.max(*v));
+            for point in points {
+                let Some(influence) = influence.get(&point) else {
+                    continue;
+                };
+                let t = if max_influence > 0.0 {
+                    influence / max_influence
+                } else {
+                    0.0
+                };
+                let other = (255.0 * (1.0 - t)) as u8;
+                ui.painter().circle_filled(
+                    point.into(),
+                    self.settings.point_radius * 1.5,
+                    egui::Color32::from_rgb(255, other, other),
+                );
+            }
+            // influence-weighted centre of the line's points
+            if let Some(center) = line.screen_points().center_of_mass(influence) {
+                let center = egui::Pos2::from(center);
+                let arm = egui::vec2(
+                    self.settings.point_radius * 3.0,
+                    self.settings.point_radius * 3.0,
+                );
+                let stroke =
+                    egui::Stroke::new(self.settings.line_thickness / 2.0, egui::Color32::RED);
+                ui.painter()
+                    .line_segment([center - arm, center + arm], stroke);
+                ui.painter().line_segment(
+                    [
+                        center + egui::vec2(-arm.x, arm.y),
+                        center + egui::vec2(arm.x, -arm.y),
+                    ],
+                    stroke,
+                );
+            }
+        }
+    }
+
+    // Starts a job for every line showing its influence whose values are missing or were
+    // computed for other points, another transform or another fit. The influence needs one
+    // regression per point, so it is computed off the UI thread.
+    fn refresh_influence(&mut self) {
+        let ids = self
+            .regression_lines
+            .iter()
+            .map(ScreenLineSegment::id)
+            .collect::<HashSet<_>>();
+        self.influence.retain(|id, _| ids.contains(id));
+        let stale = self
+            .regression_lines
+            .iter()
+            .filter(|line| line.show_influence && !self.influence_jobs.contains(&line.id()))
+            .filter_map(|line| {
+                let fingerprint = FitFingerprint::of(line);
+                match self.influence.get(&line.id()) {
+                    Some((cached, _)) if *cached == fingerprint => None,
+                    _ => Some((line.id(), fingerprint, line.regressor().clone())),
+                }
+            })
+            .collect::<Vec<_>>();
+        for (id, fingerprint, regressor) in stale {
+            let sender = self.influence_sender.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send((id, fingerprint, regressor.influence_by_point()));
+            });
+            self.influence_jobs.insert(id);
+        }
+    }
+
+    fn process_influence_results(&mut self, ctx: &egui::Context) {
+        while let Ok((id, fingerprint, influence)) = self.influence_receiver.try_recv() {
+            self.influence_jobs.remove(&id);
+            self.influence
+                .insert(id, (fingerprint, influence.into_iter().collect()));
+        }
+        self.refresh_influence();
+        if !self.influence_jobs.is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(100));
         }
     }
-}
 
-impl App {
-    fn screenshot_from_preferred(&self) -> ColorImage {
-        let screenshot: image::RgbaImage = self.preferred_monitor.capture_image().unwrap();
-        let pixels = screenshot.as_flat_samples();
-        let size = [screenshot.width() as _, screenshot.height() as _]; // needed to match usize
-        ColorImage::from_rgba_unmultiplied(size, pixels.as_slice())
+    // shaded ±1 standard deviation region of the lines that have it enabled, drawn as one
+    // quad per curve sample since egui only fills convex polygons
+    fn paint_bands(&mut self, ui: &egui::Ui) {
+        let ids = self
+            .regression_lines
+            .iter()
+            .map(ScreenLineSegment::id)
+            .collect::<HashSet<_>>();
+        self.bands.retain(|id, _| ids.contains(id));
+        for line in self.regression_lines.iter_visible().filter(|l| l.show_band) {
+            let fill = egui::Color32::from(line.draw_color).gamma_multiply(self.band_alpha);
+            let fingerprint = FitFingerprint::of(line);
+            let cached = self
+                .bands
+                .get(&line.id())
+                .filter(|(cached, confidence, _)| {
+                    *cached == fingerprint && *confidence == self.confidence_level
+                });
+            if cached.is_none() {
+                let band = match self.confidence_level {
+                    Some(confidence) => line.confidence_band(confidence, CURVE_SAMPLES),
+                    None => line.screen_band_points(CURVE_SAMPLES),
+                };
+                self.bands
+                    .insert(line.id(), (fingerprint, self.confidence_level, band));
+            }
+            let (_, _, band) = &self.bands[&line.id()];
+            paint_confidence_band(ui.painter(), band, fill);
+        }
+    }
+
+    // samples the theoretical curve over the world-space x range that is on screen; the clip
+    // rect is in screen space while the painter draws screenshot pixels under zoom and pan
+    fn paint_theoretical_curve(&self, ui: &egui::Ui) {
+        if !self.theory_enabled || self.theory_formula.trim().is_empty() {
+            return;
+        }
+        let rect = self.view_transform().inverse() * ui.clip_rect();
+        let world_xs = [
+            rect.left_top(),
+            rect.right_top(),
+            rect.left_bottom(),
+            rect.right_bottom(),
+        ]
+        .map(|corner| {
+            PointCoords::from(corner)
+                .transform(&self.current_transform)
+                .x
+                .into_inner()
+        });
+        let min_x = world_xs.iter().copied().fold(f32::INFINITY, f32::min);
+        let max_x = world_xs.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        // errors are shown next to the formula field
+        let Ok(points) = sample_formula(&self.theory_formula, (min_x, max_x), CURVE_SAMPLES * 4)
+        else {
+            return;
+        };
+        let points = points
+            .into_iter()
+            .map(|p| self.current_transform.apply_inverse(p).into())
+            .collect::<Vec<egui::Pos2>>();
+        ui.painter().extend(egui::Shape::dashed_line(
+            &points,
+            egui::Stroke::new(
+                self.settings.line_thickness / 2.0,
+                egui::Color32::LIGHT_BLUE,
+            ),
+            8.0,
+            6.0,
+        ));
+    }
+
+    fn paint_line_segments(&mut self, ui: &egui::Ui, stroke: f32) {
+        let roi = self.roi.map(|r| (r.min.into(), r.max.into()));
+        for line in self.regression_lines.iter_visible() {
+            let direction = egui::Pos2::from(line.to_unit_vector()).to_vec2();
+            if line.fit_kind() != CurveFitKind::Linear
+                && line.orientation() != LineOrientation::Vertical
+            {
+                let points = line.screen_curve_points(CURVE_SAMPLES);
+                let stroke = egui::Stroke::new(stroke, line.draw_color);
+                if let (Some(first), Some(last)) = (points.first(), points.last()) {
+                    if self.is_inside_roi(*first) && self.is_inside_roi(*last) {
+                        paint_endpoints(
+                            ui.painter(),
+                            line.endpoint_style,
+                            [(*first).into(), (*last).into()],
+                            direction,
+                            stroke,
+                        );
+                    }
+                }
+                match roi {
+                    Some(roi) => {
+                        for pair in points.windows(2) {
+                            if let Some((start, end)) = clip_segment(pair[0], pair[1], roi) {
+                                ui.painter()
+                                    .line_segment([start.into(), end.into()], stroke);
+                            }
+                        }
+                    }
+                    None => {
+                        let points = points.into_iter().map(egui::Pos2::from).collect();
+                        ui.painter().add(egui::Shape::line(points, stroke));
+                    }
+                }
+                continue;
+            }
+            // points outside of the ROI are kept, they just aren't drawn
+            let clipped;
+            let line = match self.roi {
+                Some(roi) => match line.clip_to_rect(roi) {
+                    Some(line) => {
+                        clipped = line;
+                        &clipped
+                    }
+                    None => continue,
+                },
+                None => line,
+            };
+            let points = if line.orientation() == LineOrientation::Vertical {
+                // the screen slope is infinite, but the ends already are on the line
+                [line.leftmost_pt.into(), line.rightmost_pt.into()]
+            } else {
+                let start_y = line.screen_space_slope() * line.leftmost_pt.x.into_inner()
+                    + line.screen_space_intercept();
+                let end_y = line.screen_space_slope() * line.rightmost_pt.x.into_inner()
+                    + line.screen_space_intercept();
+                [
+                    egui::Pos2::new(line.leftmost_pt.x.into_inner(), start_y),
+                    egui::Pos2::new(line.rightmost_pt.x.into_inner(), end_y),
+                ]
+            };
+            let stroke = egui::Stroke::new(stroke, line.draw_color);
+            ui.painter().add(egui::Shape::line_segment(points, stroke));
+            paint_endpoints(ui.painter(), line.endpoint_style, points, direction, stroke);
+        }
+    }
+
+    fn process_points_buffer(&mut self) {
+        if let AppState::Extending(idx) = self.state {
+            self.extend_line(idx);
+            return;
+        }
+        if self.buffered_points.len() < 2 {
+            return;
+        }
+        let mut line = ScreenLineSegment::new_from_buf(self.buffered_points.clone());
+        line.set_requested_fit(self.fit_kind);
+        let idx = self.regression_lines.add(line);
+        self.status_message = format!("Added {}", self.regression_lines[idx]);
+        self.buffered_points.clear();
+        self.record(UndoAction::AddLine(self.regression_lines[idx].name.clone()));
+    }
+
+    // like `process_points_buffer`, but mis-clicks are rejected as outliers first
+    fn process_points_buffer_ransac(&mut self) {
+        if self.buffered_points.len() < 2 {
+            return;
+        }
+        let mut line = ScreenLineSegment::new_ransac(
+            self.buffered_points.clone(),
+            RANSAC_ITERATIONS,
+            RANSAC_INLIER_THRESHOLD,
+        );
+        line.set_requested_fit(self.fit_kind);
+        let outliers = line.outliers().len();
+        let idx = self.regression_lines.add(line);
+        self.status_message = format!(
+            "Added {} ({} outliers rejected)",
+            self.regression_lines[idx], outliers
+        );
+        self.buffered_points.clear();
+        self.record(UndoAction::AddLine(self.regression_lines[idx].name.clone()));
+    }
+
+    // moves the buffered points into an existing line and refits it
+    fn extend_line(&mut self, idx: usize) {
+        self.state = AppState::Normal;
+        // the line may have been deleted while its points were being placed
+        if idx >= self.regression_lines.len() || self.buffered_points.is_empty() {
+            return;
+        }
+        let points = std::mem::take(&mut self.buffered_points);
+        self.regression_lines[idx] = self.regression_lines[idx].extend_with_points(points);
+        self.status_message = format!("Extended {}", self.regression_lines[idx]);
+        self.record(UndoAction::ExtendLine(idx));
+    }
+
+    // merges the buffered points within `radius` pixels of each other into their centroids
+    fn merge_buffered_points(&mut self, radius: f32) {
+        let before = self.buffered_points.len();
+        merge_nearby_points(&mut self.buffered_points, radius);
+        let merged = before - self.buffered_points.len();
+        if merged > 0 {
+            self.record(UndoAction::ReplacePoints);
+        }
+        self.status_message = format!("Merged {} near-duplicate points", merged);
+    }
+
+    // replaces the buffered points with their moving average along x
+    fn smooth_buffered_points(&mut self) {
+        let mut points = self.buffered_points.iter().cloned().collect::<Vec<_>>();
+        points.sort_by_key(|p| (p.x, p.y));
+        let smoothed = smooth_moving_average(&points, self.smoothing_window);
+        if smoothed.is_empty() {
+            return;
+        }
+        self.buffered_points = smoothed.into_iter().collect();
+        self.record(UndoAction::ReplacePoints);
+        self.status_message = format!("Smoothed to {} points", self.buffered_points.len());
+    }
+
+    fn export_line(&mut self, idx: usize) {
+        let request = self.regression_lines.export_request(
+            idx,
+            &self.save_dir,
+            self.csv_format,
+            self.export_settings,
+        );
+        self.pending_line_exports
+            .insert(self.regression_lines[idx].id());
+        self.send_io_request(request);
+    }
+
+    fn export_line_segments(&mut self) {
+        for request in
+            self.regression_lines
+                .export_all(&self.save_dir, self.csv_format, self.export_settings)
+        {
+            self.pending_line_exports.extend(request.line_id());
+            self.send_io_request(request);
+        }
+        self.export_measurements();
+    }
+
+    fn export_measurements(&mut self) {
+        if self.measurements.is_empty() {
+            return;
+        }
+        let file_name = format!("measurements.{}", self.export_settings.format.extension());
+        self.send_io_request(IoRequest::SaveMeasurements {
+            measurements: self.measurements.clone(),
+            file_path: self.save_dir.join(file_name),
+            settings: self.export_settings,
+        });
+    }
+
+    // what Ctrl+S does: every line with its fit in one JSON file, and the measurements
+    fn export_lines_json(&mut self) {
+        if self.regression_lines.is_empty() {
+            self.status_message = "No lines to export yet".to_owned();
+            return;
+        }
+        let settings = ExportSettings {
+            format: ExportFormat::Json,
+            ..self.export_settings
+        };
+        let request = self.regression_lines.export_all_in_one(
+            &self.save_dir,
+            &self.current_transform,
+            settings,
+        );
+        self.send_io_request(request);
+        self.export_measurements();
+    }
+
+    // what S does: the selected line, or everything when nothing is selected
+    fn export_selection(&mut self) {
+        match self.selected_line_idx {
+            Some(idx) => self.export_line(idx),
+            None => self.export_line_segments(),
+        }
+    }
+
+    fn draw_export_settings(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_export_settings;
+        let mut confirmed = false;
+        egui::Window::new("Export settings")
+            .open(&mut open)
+            .default_pos(egui::pos2(500.0, 300.0))
+            .show(ctx, |ui| {
+                let settings = &mut self.export_settings;
+                egui::ComboBox::from_label("Format")
+                    .selected_text(settings.format.display_name())
+                    .show_ui(ui, |ui| {
+                        for format in ExportFormat::ALL {
+                            ui.selectable_value(
+                                &mut settings.format,
+                                format,
+                                format.display_name(),
+                            );
+                        }
+                    });
+                egui::ComboBox::from_label("Line coordinates")
+                    .selected_text(self.csv_format.display_name())
+                    .show_ui(ui, |ui| {
+                        for csv_format in CsvFormat::ALL {
+                            ui.selectable_value(
+                                &mut self.csv_format,
+                                csv_format,
+                                csv_format.display_name(),
+                            );
+                        }
+                    });
+                ui.checkbox(
+                    &mut settings.include_headers,
+                    "Header row (lines always have one)",
+                );
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut settings.resample, "Export resampled");
+                    ui.add_enabled(
+                        settings.resample,
+                        egui::DragValue::new(&mut settings.resample_points)
+                            .range(2..=10_000)
+                            .suffix(" points"),
+                    );
+                });
+                ui.checkbox(&mut settings.append, "Append to existing files");
+                ui.checkbox(&mut settings.timestamp_suffix, "Timestamp suffix");
+                #[cfg(feature = "serde")]
+                ui.checkbox(&mut settings.transform_sidecar, "Transform side-car (JSON)");
+                ui.horizontal(|ui| {
+                    ui.label("Directory");
+                    let mut dir = self.save_dir.display().to_string();
+                    if ui.text_edit_singleline(&mut dir).changed() {
+                        self.save_dir = PathBuf::from(dir);
+                    }
+                });
+
+                ui.separator();
+                let preview = match self.selected_line_idx {
+                    Some(idx) => Some(self.regression_lines.export_path(
+                        idx,
+                        &self.save_dir,
+                        self.export_settings.format,
+                    )),
+                    None if !self.regression_lines.is_empty() => {
+                        Some(self.regression_lines.export_path(
+                            0,
+                            &self.save_dir,
+                            self.export_settings.format,
+                        ))
+                    }
+                    None => None,
+                };
+                match preview {
+                    Some(path) => {
+                        let path = export::output_path(&path, &self.export_settings);
+                        ui.label(format!("Writes e.g. {}", path.display()));
+                    }
+                    None => {
+                        ui.weak("No lines to export yet");
+                    }
+                }
+                confirmed = ui
+                    .add_enabled(
+                        !self.regression_lines.is_empty(),
+                        egui::Button::new("Export (S)"),
+                    )
+                    .clicked();
+            });
+        self.show_export_settings = open && !confirmed;
+        if confirmed {
+            self.export_selection();
+        }
+    }
+
+    // saves the window capture requested by the "Export view as PNG" button
+    fn process_view_capture(&mut self, ctx: &egui::Context) {
+        let image = ctx.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        if let Some(image) = image {
+            self.send_io_request(IoRequest::SaveView {
+                image,
+                file_path: self.save_dir.join("view.png"),
+            });
+        }
+    }
+
+    fn send_io_request(&mut self, request: IoRequest) {
+        // the IO thread lives as long as the app, so the channel can't be closed
+        self.io_sender.send(request).unwrap();
+        self.io_in_flight += 1;
+    }
+
+    fn process_io_responses(&mut self, ctx: &egui::Context) {
+        while let Ok(response) = self.io_receiver.try_recv() {
+            self.io_in_flight -= 1;
+            match response {
+                IoResponse::Saved { file_path, line_id } => {
+                    if let Some(id) = line_id {
+                        self.pending_line_exports.remove(&id);
+                    }
+                    self.status_message = format!("Saved {}", file_path.display());
+                }
+                IoResponse::Loaded {
+                    file_path,
+                    points,
+                    color,
+                } => {
+                    let mut line = ScreenLineSegment::from_transform_and_world_points(
+                        points.into_iter().collect(),
+                        self.target_transform,
+                    );
+                    line.draw_color = color;
+                    line.source = SourceKind::Loaded;
+                    let idx = self.regression_lines.add(line);
+                    self.record(UndoAction::AddLine(self.regression_lines[idx].name.clone()));
+                    self.status_message = format!("Loaded {}", file_path.display());
+                }
+                IoResponse::Failed {
+                    file_path,
+                    line_id,
+                    error,
+                } => {
+                    if let Some(id) = line_id {
+                        self.pending_line_exports.remove(&id);
+                    }
+                    self.status_message = format!("{}: {}", file_path.display(), error);
+                }
+            }
+        }
+        // the worker can't wake the UI up by itself, so poll until it is done
+        if self.io_in_flight > 0 {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+
+    // snapshots the points and lines after a change so it can be undone
+    fn record(&mut self, action: UndoAction) {
+        self.history.push(HistoryEntry {
+            points: self.buffered_points.clone(),
+            lines: self.regression_lines.clone(),
+            action: Some(action),
+        });
+    }
+
+    fn undo(&mut self) {
+        // the action is the one that led to the state being left
+        let Some(action) = self.history.past().back().and_then(|e| e.action.clone()) else {
+            return;
+        };
+        if let Some(entry) = self.history.undo() {
+            self.restore(entry, &action);
+            self.status_message = format!("Undid {}", action);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(entry) = self.history.redo() {
+            let Some(action) = entry.action.clone() else {
+                return;
+            };
+            self.restore(entry, &action);
+            self.status_message = format!("Redid {}", action);
+        }
+    }
+
+    // Point-only steps leave the lines alone, so edits that aren't recorded (colour, name,
+    // endpoint style, band) survive undoing a click.
+    fn restore(&mut self, entry: HistoryEntry, action: &UndoAction) {
+        self.buffered_points = entry.points;
+        if action.changes_lines() {
+            self.regression_lines = entry.lines;
+            // indices are stale once the lines are swapped out, pending exports go by id
+            self.selected_line_idx = None;
+            self.checked_lines.clear();
+        }
+    }
+
+    fn undo_calibration(&mut self) {
+        if let Some(transform) = self.transform_history.undo() {
+            self.set_transform(transform);
+            self.transform_blend = 1.0;
+        }
+    }
+
+    fn redo_calibration(&mut self) {
+        if let Some(transform) = self.transform_history.redo() {
+            self.set_transform(transform);
+            self.transform_blend = 1.0;
+        }
+    }
+
+    fn transform_line_segments(&mut self) {
+        self.regression_lines.iter_mut().for_each(|line| {
+            line.set_regression_mode(self.regression_mode);
+            line.set_fit_space(self.fit_space);
+            line.transform_line(&self.current_transform);
+        });
+    }
+
+    // returns a type-erased iterator over the points to show based on state
+    fn get_buffer_iterator(&self) -> Box<dyn Iterator<Item = &PointCoords> + '_> {
+        match &self.state {
+            AppState::Measurement => Box::from(self.measurement_buffer.iter()),
+            AppState::TickCalibration => Box::from(
+                self.horizontal_ticks
+                    .iter()
+                    .chain(self.vertical_ticks.iter())
+                    .map(|tick| &tick.screen_pos),
+            ),
+            AppState::DistanceMeasurement(points) | AppState::AngleMeasurement(points) => {
+                Box::from(points.iter().flatten())
+            }
+            AppState::PolygonRoiSelection(vertices) => Box::from(vertices.iter()),
+            AppState::Normal
+            | AppState::Extending(_)
+            | AppState::RoiSelection(_)
+            | AppState::Delete
+            | AppState::Snap
+            | AppState::ColorPick
+            | AppState::MonitorSelect
+            | AppState::CropSelection(_) => Box::from(
+                self.buffered_points
+                    .iter()
+                    .take(self.replay_step.unwrap_or(usize::MAX)),
+            ),
+        }
+    }
+
+    // pushes a point to the buffer based on the current state
+    fn push_to_buffer(&mut self, point: PointCoords) {
+        // NaN or infinite positions would corrupt the hash set and the regression
+        if !point.is_finite() {
+            return;
+        }
+        // the screenshot is drawn at the origin, keep points on it
+        let point = match &self.screenshot_texture_handle {
+            Some(texture) => {
+                let [width, height] = texture.size();
+                point.clamp(
+                    PointCoords::new(0.0, 0.0),
+                    PointCoords::new(width as f32, height as f32),
+                )
+            }
+            None => point,
+        };
+        match &mut self.state {
+            AppState::Normal | AppState::Extending(_) => {
+                if self.is_inside_roi(point)
+                    && self.passes_color_filter(point)
+                    && self.buffered_points.insert(point)
+                {
+                    self.record(UndoAction::AddPoint(point));
+                }
+            }
+            AppState::Measurement => {
+                let _ = self.measurement_buffer.push_back(point);
+            }
+            AppState::RoiSelection(None) => {
+                self.state = AppState::RoiSelection(Some(egui::Rect::from_min_max(
+                    point.into(),
+                    point.into(),
+                )));
+            }
+            AppState::RoiSelection(Some(corner)) => {
+                self.roi = Some(egui::Rect::from_two_pos(corner.min, point.into()));
+                self.state = AppState::Normal;
+            }
+            AppState::PolygonRoiSelection(vertices) => {
+                let closes = vertices.len() >= 3
+                    && vertices[0].distance_to(&point) <= NEAREST_POINT_TOLERANCE;
+                if closes {
+                    self.roi_polygon = Some(std::mem::take(vertices));
+                    self.state = AppState::Normal;
+                } else {
+                    vertices.push(point);
+                }
+            }
+            AppState::DistanceMeasurement(points) => {
+                if let Some(result) = Self::measure(
+                    points,
+                    point,
+                    MeasurementKind::Distance,
+                    &self.target_transform,
+                    self.settings.point_radius,
+                ) {
+                    self.measurements.push(result);
+                }
+            }
+            AppState::AngleMeasurement(points) => {
+                if let Some(result) = Self::measure(
+                    points,
+                    point,
+                    MeasurementKind::Angle,
+                    &self.target_transform,
+                    self.settings.point_radius,
+                ) {
+                    self.measurements.push(result);
+                }
+            }
+            AppState::Delete => {
+                self.remove_nearest_buffered_point(point);
+            }
+            AppState::ColorPick => {
+                self.target_color = self.sample_color(point);
+                self.color_filter_enabled = self.target_color.is_some();
+                self.state = AppState::Normal;
+            }
+            AppState::Snap => {
+                let snapped = self.snap_to_nearest_line(point);
+                if self.is_inside_roi(snapped) && self.buffered_points.insert(snapped) {
+                    self.record(UndoAction::AddPoint(snapped));
+                }
+            }
+            // ticks are placed with the H and V hotkeys
+            AppState::TickCalibration => {}
+            AppState::MonitorSelect | AppState::CropSelection(_) => {}
+        }
+    }
+
+    fn is_inside_roi(&self, point: PointCoords) -> bool {
+        self.roi.map_or(true, |roi| roi.contains(point.into()))
+            && self
+                .roi_polygon
+                .as_ref()
+                .map_or(true, |polygon| point_in_polygon(point, polygon))
+    }
+
+    // average colour of the screenshot in a small square around the point
+    fn sample_color(&self, point: PointCoords) -> Option<egui::Color32> {
+        let image = self.screenshot_image.as_ref()?;
+        let (x, y) = (point.x.into_inner(), point.y.into_inner());
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let [width, height] = image.size;
+        let mut sum = [0usize; 3];
+        let mut count = 0;
+        for py in y.saturating_sub(COLOR_SAMPLE_RADIUS)..=(y + COLOR_SAMPLE_RADIUS).min(height - 1)
+        {
+            for px in
+                x.saturating_sub(COLOR_SAMPLE_RADIUS)..=(x + COLOR_SAMPLE_RADIUS).min(width - 1)
+            {
+                let pixel = image.pixels[py * width + px];
+                sum[0] += pixel.r() as usize;
+                sum[1] += pixel.g() as usize;
+                sum[2] += pixel.b() as usize;
+                count += 1;
+            }
+        }
+        (count > 0).then(|| {
+            egui::Color32::from_rgb(
+                (sum[0] / count) as u8,
+                (sum[1] / count) as u8,
+                (sum[2] / count) as u8,
+            )
+        })
+    }
+
+    fn passes_color_filter(&self, point: PointCoords) -> bool {
+        match (self.color_filter_enabled, self.target_color) {
+            (true, Some(target)) => self
+                .sample_color(point)
+                .is_some_and(|color| colors_match(color, target, self.color_tolerance)),
+            _ => true,
+        }
+    }
+
+    fn remove_nearest_buffered_point(&mut self, target: PointCoords) {
+        if !remove_nearest(&mut self.buffered_points, target, NEAREST_POINT_TOLERANCE) {
+            return;
+        }
+        // the last recorded state still has the point, which names the undo step
+        let removed = self
+            .history
+            .past()
+            .back()
+            .and_then(|entry| {
+                entry
+                    .points
+                    .iter()
+                    .find(|p| !self.buffered_points.contains(*p))
+            })
+            .copied()
+            .unwrap_or(target);
+        self.record(UndoAction::RemovePoint(removed));
+    }
+
+    // closest visible regression line in screen space as (line, foot of the perpendicular,
+    // perpendicular distance)
+    fn nearest_line(&self, point: PointCoords) -> Option<(&ScreenLineSegment, PointCoords, f32)> {
+        self.regression_lines
+            .iter_visible()
+            .map(|line| {
+                let foot = point
+                    .project_onto_line(line.screen_space_slope(), line.screen_space_intercept());
+                (line, foot, point.distance_to(&foot))
+            })
+            .filter(|(_, foot, _)| foot.is_finite())
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+    }
+
+    // orthogonally projects the point onto the closest regression line in screen space
+    fn snap_to_nearest_line(&self, point: PointCoords) -> PointCoords {
+        self.nearest_line(point).map_or(point, |(_, foot, _)| foot)
+    }
+
+    // equation of the line under the cursor, drawn next to it, and the circle around its points
+    fn paint_line_tooltip(&self, ui: &egui::Ui) {
+        let Some(pos) = ui.input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+        let pos = self.screenshot_pos(pos);
+        let Some((line, _, distance)) = self.nearest_line(pos.into()) else {
+            return;
+        };
+        if distance > self.settings.line_thickness * 4.0 {
+            return;
+        }
+        let (center, radius) = line.bounding_circle();
+        ui.painter().circle_stroke(
+            center.into(),
+            radius,
+            egui::Stroke::new(
+                1.0,
+                egui::Color32::from(line.draw_color).gamma_multiply(0.6),
+            ),
+        );
+        let text = ui.fonts(|fonts| {
+            egui::Shape::text(
+                fonts,
+                pos + egui::vec2(12.0, -12.0),
+                egui::Align2::LEFT_BOTTOM,
+                line.transformed_line_equation(),
+                egui::FontId::proportional(14.0),
+                egui::Color32::YELLOW,
+            )
+        });
+        ui.painter().add(text);
+    }
+
+    fn paint_roi(&self, ui: &egui::Ui) {
+        let hover_pos = ui.input(|i| i.pointer.hover_pos());
+        let rect = match (&self.state, hover_pos.map(|pos| self.screenshot_pos(pos))) {
+            (AppState::RoiSelection(Some(corner)), Some(pos)) => {
+                Some(egui::Rect::from_two_pos(corner.min, pos))
+            }
+            _ => self.roi,
+        };
+        let stroke = egui::Stroke::new(self.settings.line_thickness / 2.0, egui::Color32::YELLOW);
+        if let Some(rect) = rect {
+            ui.painter().rect_stroke(rect, 0.0, stroke);
+        }
+        match (&self.state, &self.roi_polygon) {
+            // the unfinished outline follows the cursor
+            (AppState::PolygonRoiSelection(vertices), _) => {
+                let outline = vertices
+                    .iter()
+                    .map(|&p| p.into())
+                    .chain(hover_pos.map(|pos| self.screenshot_pos(pos)))
+                    .collect();
+                ui.painter().add(egui::Shape::line(outline, stroke));
+            }
+            (_, Some(polygon)) => {
+                let outline = polygon.iter().map(|&p| p.into()).collect();
+                ui.painter().add(egui::Shape::closed_line(outline, stroke));
+            }
+            _ => {}
+        }
+    }
+
+    // records a measurement point and returns the result once both points are placed
+    fn measure(
+        points: &mut [Option<PointCoords>; 2],
+        point: PointCoords,
+        kind: MeasurementKind,
+        transform: &PointTransform,
+        // a second point closer than this to the first one is ignored
+        min_distance: f32,
+    ) -> Option<MeasurementResult> {
+        match *points {
+            // a zero-length measurement has no meaningful angle
+            [Some(first), None] if first.approx_eq(&point, min_distance) => None,
+            [Some(first), None] => {
+                points[1] = Some(point);
+                Some(MeasurementResult::from_screen_points(
+                    kind, first, point, transform,
+                ))
+            }
+            _ => {
+                *points = [Some(point), None];
+                None
+            }
+        }
+    }
+
+    fn paint_measurements(&self, ui: &egui::Ui) {
+        for measurement in &self.measurements {
+            ui.painter().text(
+                measurement.screen_pos.into(),
+                egui::Align2::CENTER_BOTTOM,
+                &measurement.label,
+                egui::FontId::proportional(14.0),
+                egui::Color32::YELLOW,
+            );
+        }
+    }
+
+    fn paint_intersection_labels(&self, ui: &egui::Ui) {
+        if !self.show_intersection_labels {
+            return;
+        }
+        let labels = self
+            .regression_lines
+            .intersection_rows()
+            .into_iter()
+            .filter_map(|row| {
+                let pos = row.world_pos?;
+                let text = format!(
+                    "{} × {} ({:.3}, {:.3})",
+                    row.line_a, row.line_b, pos.x, pos.y
+                );
+                Some(WorldSpaceLabel::new(pos, text, RGBColor::new(255, 255, 0)))
+            })
+            .collect::<Vec<_>>();
+        draw_world_labels(&labels, ui.painter(), &self.current_transform);
+    }
+
+    // starts easing the displayed transform from where it is now towards `transform`
+    fn set_transform(&mut self, transform: PointTransform) {
+        self.transform_animation_start = self.current_transform;
+        self.target_transform = transform;
+        self.lerp_t = 0.0;
+    }
+
+    fn advance_transform_animation(&mut self, ctx: &egui::Context) {
+        if self.lerp_t >= 1.0 {
+            return;
+        }
+        let duration = self.settings.transform_animation_secs;
+        self.lerp_t = if duration > 0.0 {
+            (self.lerp_t + ctx.input(|i| i.stable_dt) / duration).min(1.0)
+        } else {
+            1.0
+        };
+        // smoothstep, so the lines neither start nor stop abruptly
+        let eased = self.lerp_t * self.lerp_t * (3.0 - 2.0 * self.lerp_t);
+        self.current_transform = PointTransform::interpolate_between(
+            self.transform_animation_start,
+            self.target_transform,
+            eased,
+        );
+        if self.lerp_t < 1.0 {
+            ctx.request_repaint();
+        }
+    }
+
+    // makes `transform` current and remembers it for blending
+    fn apply_calibration(&mut self, transform: PointTransform) {
+        self.set_transform(transform);
+        self.transform_history.push(transform);
+        self.transform_blend = 1.0;
+    }
+
+    // one more (screen, world) calibration pair, measured next in calibration mode
+    fn add_calibration_pair(&mut self) {
+        let pairs = self.measurement_buffer_rw_s.len() + 1;
+        drop(self.measurement_buffer.set_max_len(pairs));
+        drop(self.measurement_buffer_real_world.set_max_len(pairs));
+        drop(self.measurement_buffer_rw_s.set_max_len(pairs));
+        let _ = self
+            .measurement_buffer_real_world
+            .push_back(PointCoords::new(0.0, 0.0));
+        let _ = self
+            .measurement_buffer_rw_s
+            .push_back(PointCoordsStringy::new_formatted(
+                0.0,
+                0.0,
+                self.settings.calibration_precision,
+            ));
+    }
+
+    // calibrates from a transform JSON, e.g. a side-car written next to an exported line
+    fn load_transform(&mut self, path: &std::path::Path) {
+        match export::read_transform(path) {
+            Ok(transform) => {
+                self.apply_calibration(transform);
+                self.status_message = format!("Loaded transform from {}", path.display());
+            }
+            Err(e) => {
+                self.status_message =
+                    format!("Failed to load transform from {}: {}", path.display(), e);
+            }
+        }
     }
 
-    fn draw_screenshot_layer(&mut self, ui: &mut egui::Ui) {
-        if self.screenshot_texture_handle.is_none() {
-            let handle = ui.ctx().load_texture(
-                SCREENSHOT_TEXTURE,
-                self.screenshot_from_preferred(),
-                Default::default(),
-            );
-            self.screenshot_texture_handle = Some(handle);
+    // starts a tick on the given axis at the cursor, its value is asked for in a popup
+    fn begin_tick(&mut self, ctx: &egui::Context, axis: Axis) {
+        if let Some(pos) = ctx.input(|i| i.pointer.hover_pos()) {
+            self.pending_tick = Some(PendingTick {
+                axis,
+                screen_pos: self.screenshot_pos(pos).into(),
+                value: String::new(),
+            });
         }
+    }
 
-        // unwrap is safe because we just set it if it was None
-        ui.image(egui::load::SizedTexture::from_handle(
-            self.screenshot_texture_handle.as_ref().unwrap(),
+    // finds evenly spaced tick marks in the screenshot (within the ROI, if any) and asks
+    // for the values of the first two
+    fn detect_ticks(&mut self, axis: Axis) {
+        let Some(image) = self.screenshot_image.as_ref() else {
+            return;
+        };
+        let [width, height] = image.size;
+        let pixels = image
+            .pixels
+            .iter()
+            .flat_map(|c| c.to_srgba_unmultiplied())
+            .collect();
+        // the buffer length always matches, the pixels came from an image of this size
+        let rgba = image::RgbaImage::from_raw(width as u32, height as u32, pixels).unwrap();
+        let area = self.roi.unwrap_or(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width as f32, height as f32),
+        ));
+        let area = area.intersect(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width as f32, height as f32),
         ));
+        let cropped = image::imageops::crop_imm(
+            &rgba,
+            area.min.x as u32,
+            area.min.y as u32,
+            area.width() as u32,
+            area.height() as u32,
+        )
+        .to_image();
+        let Some((spacing, first)) = detect_axis_scale(&cropped, axis) else {
+            self.status_message = "No evenly spaced tick marks found".to_owned();
+            return;
+        };
+        let tick = |offset: f32| {
+            let screen_pos = match axis {
+                Axis::Horizontal => PointCoords::new(area.min.x + offset, area.center().y),
+                Axis::Vertical => PointCoords::new(area.center().x, area.min.y + offset),
+            };
+            PendingTick {
+                axis,
+                screen_pos,
+                value: String::new(),
+            }
+        };
+        self.pending_tick = Some(tick(first));
+        self.queued_tick = Some(tick(first + spacing));
+        self.state = AppState::TickCalibration;
+        self.status_message = format!("Detected ticks every {:.1} px", spacing);
     }
 
-    fn paint_buffered_points(&mut self, ui: &egui::Ui) {
-        for point in self.get_buffer_iterator() {
-            ui.painter()
-                .add(egui::Shape::Circle(egui::epaint::CircleShape {
-                    center: (*point).into(),
-                    radius: POINT_RADIUS,
-                    fill: egui::Color32::RED,
-                    stroke: Default::default(),
-                }));
+    // stores a finished tick and calibrates once both axes have enough of them
+    fn commit_tick(&mut self, axis: Axis, tick: AxisTick) {
+        let ticks = match axis {
+            Axis::Horizontal => &mut self.horizontal_ticks,
+            Axis::Vertical => &mut self.vertical_ticks,
+        };
+        let _ = ticks.push_back(tick);
+        if !self.horizontal_ticks.is_full() || !self.vertical_ticks.is_full() {
+            return;
+        }
+        let horizontal = [self.horizontal_ticks[0], self.horizontal_ticks[1]];
+        let vertical = [self.vertical_ticks[0], self.vertical_ticks[1]];
+        match PointTransform::from_axis_ticks(horizontal, vertical) {
+            Ok(transform) => {
+                self.apply_calibration(transform);
+                self.status_message = "Calibrated from axis ticks".to_owned();
+                self.horizontal_ticks.clear();
+                self.vertical_ticks.clear();
+                self.state = AppState::Normal;
+            }
+            Err(e) => {
+                self.status_message = e.to_string();
+            }
         }
     }
 
-    fn paint_line_segments(&mut self, ui: &egui::Ui, stroke: f32) {
-        for line in &self.regression_lines {
-            let start_y = line.screen_space_slope() * line.leftmost_pt.x.into_inner()
-                + line.screen_space_intercept();
-            let end_y = line.screen_space_slope() * line.rightmost_pt.x.into_inner()
-                + line.screen_space_intercept();
-            let start_pos = egui::Pos2::new(line.leftmost_pt.x.into_inner(), start_y);
-            let end_pos = egui::Pos2::new(line.rightmost_pt.x.into_inner(), end_y);
-            let points = [start_pos, end_pos];
-            ui.painter().add(egui::Shape::line_segment(
-                points,
-                egui::Stroke::new(stroke, line.draw_color),
-            ));
+    fn open_monitor_select(&mut self) {
+        if let Ok(monitors) = Monitor::all() {
+            self.monitors = monitors;
         }
+        self.state = AppState::MonitorSelect;
     }
 
-    fn process_points_buffer(&mut self) {
-        if self.buffered_points.len() < 2 {
+    fn draw_monitor_select(&mut self, ctx: &egui::Context) {
+        if !matches!(self.state, AppState::MonitorSelect) {
             return;
         }
-        self.regression_lines.push(ScreenLineSegment::new_from_buf(
-            self.buffered_points.clone(),
-        ));
-        self.buffered_points.clear();
+        let mut selected = None;
+        egui::Window::new("Select monitor")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                for (idx, monitor) in self.monitors.iter().enumerate() {
+                    let label = format!(
+                        "{} ({}×{}){}",
+                        monitor.name(),
+                        monitor.width(),
+                        monitor.height(),
+                        if monitor.is_primary() {
+                            ", primary"
+                        } else {
+                            ""
+                        }
+                    );
+                    let current = matches!(
+                        &self.screenshot_source,
+                        ScreenshotSource::LiveCapture(m) if m.id() == monitor.id()
+                    );
+                    if ui.selectable_label(current, label).clicked() {
+                        selected = Some(idx);
+                    }
+                }
+            });
+        if let Some(idx) = selected {
+            let monitor = self.monitors[idx].clone();
+            self.set_screenshot_source(ctx, ScreenshotSource::LiveCapture(monitor), None);
+            self.state = AppState::Normal;
+        }
     }
 
-    fn transform_line_segments(&mut self) {
-        self.regression_lines.iter_mut().for_each(|line| {
-            line.transform_line(&self.current_transform);
-        });
+    fn draw_tick_popup(&mut self, ctx: &egui::Context) {
+        let view = self.view_transform();
+        let Some(pending) = self.pending_tick.as_mut() else {
+            return;
+        };
+        let title = match pending.axis {
+            Axis::Horizontal => "Horizontal tick value",
+            Axis::Vertical => "Vertical tick value",
+        };
+        let mut finished = None;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .default_pos(view * egui::Pos2::from(pending.screen_pos) + egui::vec2(10.0, 10.0))
+            .show(ctx, |ui| {
+                ui.add(numeric_text_edit(&mut pending.value))
+                    .request_focus();
+                ui.horizontal(|ui| {
+                    let value = pending.value.parse::<f32>().ok();
+                    let enter = ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui
+                        .add_enabled(value.is_some(), egui::Button::new("OK"))
+                        .clicked()
+                        || (enter && value.is_some())
+                    {
+                        finished = value.map(Some);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        finished = Some(None);
+                    }
+                });
+            });
+        if let Some(value) = finished {
+            let pending = self.pending_tick.take().unwrap();
+            // cancelling drops the queued tick as well
+            self.pending_tick = self.queued_tick.take().filter(|_| value.is_some());
+            if let Some(value) = value {
+                self.commit_tick(
+                    pending.axis,
+                    AxisTick {
+                        screen_pos: pending.screen_pos,
+                        value,
+                    },
+                );
+            }
+        }
     }
 
-    // returns a type-erased iterator over the points to show based on state
-    fn get_buffer_iterator(&self) -> Box<dyn Iterator<Item = &PointCoords> + '_> {
-        match self.gathering_state {
-            PointGatheringState::Normal => Box::from(self.buffered_points.iter()),
-            PointGatheringState::Measurement => Box::from(self.measurement_buffer.iter()),
+    // second, independently editable copy of the lines drawn over a scaled down screenshot
+    fn draw_duplicate_session(&mut self, ctx: &egui::Context) {
+        let (Some(lines), Some(texture)) = (
+            self.duplicate_session.as_mut(),
+            self.screenshot_texture_handle.as_ref(),
+        ) else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Duplicate session")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                lines.retain(|line| {
+                    ui.horizontal(|ui| {
+                        let keep = !ui.button("❌").clicked();
+                        ui.add_enabled(
+                            false,
+                            egui::Button::new(" ".repeat(8)).fill(line.draw_color),
+                        );
+                        ui.label(line.transformed_line_equation());
+                        keep
+                    })
+                    .inner
+                });
+                let size = texture.size_vec2() * DUPLICATE_SESSION_SCALE;
+                let rect = ui
+                    .image(egui::load::SizedTexture::new(texture.id(), size))
+                    .rect;
+                let painter = ui.painter_at(rect);
+                let to_view = |p: PointCoords| {
+                    rect.min + egui::Pos2::from(p).to_vec2() * DUPLICATE_SESSION_SCALE
+                };
+                for line in lines.iter() {
+                    let points = line
+                        .screen_curve_points(CURVE_SAMPLES)
+                        .into_iter()
+                        .map(to_view)
+                        .collect();
+                    painter.add(egui::Shape::line(
+                        points,
+                        egui::Stroke::new(self.settings.line_thickness / 2.0, line.draw_color),
+                    ));
+                }
+            });
+        if !open {
+            self.duplicate_session = None;
         }
     }
 
-    // pushes a point to the buffer based on the current state
-    fn push_to_buffer(&mut self, point: PointCoords) {
-        match self.gathering_state {
-            PointGatheringState::Normal => {
-                self.buffered_points.insert(point);
-            }
-            PointGatheringState::Measurement => {
-                let _ = self.measurement_buffer.push_back(point);
-            }
-        }
+    // brings the next window of the focus cycle to the front
+    fn cycle_window_focus(&mut self, ctx: &egui::Context) {
+        self.focused_window = (self.focused_window + 1) % FOCUS_CYCLE_WINDOWS.len();
+        let title = FOCUS_CYCLE_WINDOWS[self.focused_window];
+        // windows live on the middle layer, keyed by their title
+        ctx.move_to_top(egui::LayerId::new(
+            egui::Order::Middle,
+            egui::Id::new(title),
+        ));
+    }
+
+    fn draw_status_bar(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("Mode: {}", self.state.display_name()));
+                ui.separator();
+                let modes = [
+                    AppState::Normal,
+                    AppState::Measurement,
+                    AppState::RoiSelection(None),
+                    AppState::PolygonRoiSelection(Vec::new()),
+                    AppState::DistanceMeasurement([None; 2]),
+                    AppState::AngleMeasurement([None; 2]),
+                    AppState::Delete,
+                    AppState::Snap,
+                    AppState::TickCalibration,
+                    AppState::CropSelection(None),
+                ];
+                for mode in modes {
+                    let selected = self.state.is_same_mode(&mode);
+                    if ui.selectable_label(selected, mode.display_name()).clicked() && !selected {
+                        self.state = mode;
+                    }
+                }
+                ui.separator();
+                ui.checkbox(&mut self.live_capture, "Live capture");
+                if self.live_capture {
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.live_capture_interval_ms,
+                            LIVE_CAPTURE_INTERVAL_MS.clone(),
+                        )
+                        .suffix(" ms"),
+                    );
+                }
+                let padlock = if self.viewport_locked { "🔒" } else { "🔓" };
+                ui.toggle_value(&mut self.viewport_locked, padlock)
+                    .on_hover_text("Lock zoom and pan");
+                let zoomed = self.zoom != 1.0 || self.pan_offset != egui::Vec2::ZERO;
+                if zoomed
+                    && !self.viewport_locked
+                    && ui
+                        .button(format!("Reset view ({:.0}%)", self.zoom * 100.0))
+                        .clicked()
+                {
+                    self.zoom = 1.0;
+                    self.pan_offset = egui::Vec2::ZERO;
+                }
+                if (self.roi.is_some() || self.roi_polygon.is_some())
+                    && ui.button("Clear ROI").clicked()
+                {
+                    self.roi = None;
+                    self.roi_polygon = None;
+                }
+                if self.crop_rect.is_some() && ui.button("Clear crop").clicked() {
+                    self.set_crop(ctx, None);
+                }
+                if !self.measurements.is_empty() && ui.button("Clear measurements").clicked() {
+                    self.measurements.clear();
+                }
+                if ui.button("Change monitor").clicked() {
+                    self.open_monitor_select();
+                }
+                if ui.button("Load image…").clicked() {
+                    self.pick_image_file(ctx);
+                }
+                if ui.button("Export report").clicked() {
+                    let path = self.save_dir.join("report.md");
+                    self.status_message = match report::export_session_report(self, &path) {
+                        Ok(()) => format!("Saved {}", path.display()),
+                        Err(e) => format!("{}: {}", path.display(), e),
+                    };
+                }
+                // the capture arrives as an input event on a later frame
+                if ui.button("Export view as PNG").clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+                }
+                ui.separator();
+                match &self.transient_status {
+                    Some((message, expires)) if *expires > Instant::now() => {
+                        ui.label(message);
+                        ctx.request_repaint_after(*expires - Instant::now());
+                    }
+                    _ => {
+                        self.transient_status = None;
+                        ui.label(&self.status_message);
+                    }
+                }
+            });
+        });
     }
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.process_view_capture(ctx);
+        self.advance_replay(ctx);
+        self.advance_transform_animation(ctx);
+        self.process_io_responses(ctx);
+        self.process_influence_results(ctx);
+        self.draw_status_bar(ctx);
+
         egui::CentralPanel::default()
             .frame(egui::Frame::none())
             .show(ctx, |ui| {
-                self.draw_screenshot_layer(ui);
-                if let Some(pos) = ui.input(secondary_btn_click_pos) {
-                    self.push_to_buffer(pos.into());
-                }
-                self.paint_buffered_points(ui);
+                self.update_view(ui);
+                let layer = egui::LayerId::new(egui::Order::Background, SCREENSHOT_LAYER.into());
+                ctx.set_transform_layer(layer, self.view_transform());
+                ui.with_layer_id(layer, |ui| {
+                    self.draw_screenshot_layer(ui);
+                    if let Some(pos) = ui.input(secondary_btn_click_pos) {
+                        self.push_to_buffer(self.screenshot_pos(pos).into());
+                    }
+                    // before painting, so the deleted point is gone in this frame already
+                    if let Some(pos) = ui.input(delete_click_pos) {
+                        self.remove_nearest_buffered_point(self.screenshot_pos(pos).into());
+                    }
+                    self.update_crop_selection(ui);
+                    self.paint_buffered_points(ui);
+                    self.paint_roi(ui);
+                    self.paint_measurements(ui);
+                    self.paint_intersection_labels(ui);
 
-                // if l is pressed calculate regression line and clear the points buffer
-                if ctx.input(|i| i.key_pressed(egui::Key::L)) {
-                    self.process_points_buffer();
-                }
+                    // if l is pressed calculate regression line and clear the points buffer
+                    if hotkey_pressed(ctx, egui::Key::L) {
+                        self.process_points_buffer();
+                    }
+                    // r does the same, ignoring the points that don't fit the majority
+                    if hotkey_pressed(ctx, egui::Key::R) {
+                        self.process_points_buffer_ransac();
+                    }
 
-                self.transform_line_segments();
+                    // ctrl+shift+s saves the session, ctrl+shift+o opens one
+                    #[cfg(feature = "serde")]
+                    if shortcut_pressed(
+                        ctx,
+                        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                        egui::Key::S,
+                    ) {
+                        self.pick_session_to_save();
+                    } else if shortcut_pressed(
+                        ctx,
+                        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                        egui::Key::O,
+                    ) {
+                        self.pick_session_to_load(ctx);
+                    }
 
-                // paint line segments
-                self.paint_line_segments(ui, LINE_THICKNESS);
+                    // ctrl+s exports every line with its fit to a single json file (and the
+                    // measurements), s the raw points of the selected line, or of everything
+                    // when nothing is selected
+                    if shortcut_pressed(ctx, egui::Modifiers::COMMAND, egui::Key::S) {
+                        self.export_lines_json();
+                    } else if hotkey_pressed(ctx, egui::Key::S) {
+                        self.export_selection();
+                    }
 
-                if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
-                    std::process::exit(0);
-                }
+                    self.transform_line_segments();
+
+                    // paint line segments, bands first so they stay behind the lines
+                    self.paint_bands(ui);
+                    self.paint_line_segments(ui, self.settings.line_thickness);
+                    self.paint_theoretical_curve(ui);
+                    self.paint_influence(ui);
+                    self.paint_line_tooltip(ui);
+
+                    // h and v place a horizontal or vertical axis tick under the cursor
+                    if matches!(self.state, AppState::TickCalibration)
+                        && self.pending_tick.is_none()
+                    {
+                        if hotkey_pressed(ctx, egui::Key::H) {
+                            self.begin_tick(ctx, Axis::Horizontal);
+                        } else if hotkey_pressed(ctx, egui::Key::V) {
+                            self.begin_tick(ctx, Axis::Vertical);
+                        }
+                    }
+
+                    // ctrl+shift+z has to be checked first, ctrl+z would match it as well
+                    if shortcut_pressed(
+                        ctx,
+                        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                        egui::Key::Z,
+                    ) || shortcut_pressed(ctx, egui::Modifiers::COMMAND, egui::Key::Y)
+                    {
+                        self.redo();
+                    } else if shortcut_pressed(ctx, egui::Modifiers::COMMAND, egui::Key::Z) {
+                        self.undo();
+                    }
+
+                    if !ctx.wants_keyboard_input() && ctx.input(paste_released) {
+                        self.import_screenshot_from_clipboard(ctx);
+                    }
+
+                    if hotkey_pressed(ctx, egui::Key::Tab) {
+                        self.cycle_window_focus(ctx);
+                    }
+
+                    if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        std::process::exit(0);
+                    }
+                });
             });
 
         egui::Window::new("Buffered points").show(ctx, |ui| {
+            if let Some(mean) = self.buffered_points.mean_position() {
+                let mean_rw = mean.transform(&self.target_transform);
+                ui.label(format!("Mean position: ({}, {})", mean_rw.x, mean_rw.y));
+            }
+            ui.horizontal(|ui| {
+                if self.replay_step.is_some() {
+                    if ui.button("Stop").clicked() {
+                        self.replay_step = None;
+                    }
+                } else if ui
+                    .add_enabled(
+                        !self.buffered_points.is_empty(),
+                        egui::Button::new("Replay"),
+                    )
+                    .clicked()
+                {
+                    self.replay_step = Some(0);
+                    self.last_replay_step = Instant::now();
+                }
+                ui.add(
+                    egui::Slider::new(&mut self.replay_interval_ms, REPLAY_INTERVAL_MS.clone())
+                        .suffix(" ms"),
+                );
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                    .clicked()
+                {
+                    self.undo();
+                }
+                if ui
+                    .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                    .clicked()
+                {
+                    self.redo();
+                }
+                if ui
+                    .add_enabled(
+                        self.buffered_points.len() >= 2,
+                        egui::Button::new("Deduplicate"),
+                    )
+                    .on_hover_text("Merge points closer than a couple of pixels")
+                    .clicked()
+                {
+                    self.merge_buffered_points(DUPLICATE_POINT_TOLERANCE);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        self.buffered_points.len() >= 2,
+                        egui::Button::new("Cluster nearby"),
+                    )
+                    .on_hover_text("Merge the points within the radius into their centroids")
+                    .clicked()
+                {
+                    self.merge_buffered_points(self.cluster_radius);
+                }
+                ui.add(
+                    egui::Slider::new(&mut self.cluster_radius, CLUSTER_RADIUS.clone())
+                        .text("radius")
+                        .suffix(" px"),
+                );
+            });
+            ui.checkbox(&mut self.polar_display, "Polar display");
             ui.label("Buffered points:");
             for point in self.get_buffer_iterator() {
-                let point_rw = point.transform(&self.current_transform);
-                ui.label(format!("({}, {})", point_rw.x, point_rw.y));
+                let point_rw = point.transform(&self.target_transform);
+                if self.polar_display {
+                    let r = point_rw.distance_to(&PointCoords::new(0.0, 0.0));
+                    ui.label(format!(
+                        "(r = {}, θ = {:.2}°)",
+                        r,
+                        point_rw.angle_to_origin()
+                    ));
+                } else {
+                    ui.label(format!("({}, {})", point_rw.x, point_rw.y));
+                }
             }
         });
 
@@ -201,72 +2637,781 @@ impl eframe::App for App {
             .show(ctx, |ui| {
                 ui.label("Line equations:");
                 let mut keep = vec![true; self.regression_lines.len()];
-                for (idx, line) in self.regression_lines.iter().enumerate() {
+                let mut export_requested = Vec::new();
+                let mut extend_requested = None;
+                let mut snap_requested = None;
+                if !self.checked_lines.is_empty() {
                     ui.horizontal(|ui| {
+                        if ui.button("Delete selected").clicked() {
+                            for idx in &self.checked_lines {
+                                keep[*idx] = false;
+                            }
+                        }
+                        if ui.button("Export selected").clicked() {
+                            export_requested.extend(self.checked_lines.iter().copied());
+                        }
+                        if ui.button("Uncheck all").clicked() {
+                            self.checked_lines.clear();
+                        }
+                    });
+                }
+                for (idx, line) in self.regression_lines.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let mut checked = self.checked_lines.contains(&idx);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                self.checked_lines.insert(idx);
+                            } else {
+                                self.checked_lines.remove(&idx);
+                            }
+                        }
                         if ui.button("❌").clicked() {
                             keep[idx] = false;
                         }
+                        if ui
+                            .add_enabled(
+                                !self.pending_line_exports.contains(&line.id()),
+                                egui::Button::new("💾"),
+                            )
+                            .on_hover_text("Export this line")
+                            .clicked()
+                        {
+                            export_requested.push(idx);
+                        }
                         ui.add_enabled(
                             false,
                             egui::Button::new(" ".repeat(8)).fill(line.draw_color),
                         );
-                        ui.label(line.transformed_line_equation());
+                        ui.checkbox(&mut line.visible, "").on_hover_text("Visible");
+                        ui.label(&line.name);
+                        let screen_length = line.length_in_screen_space();
+                        let selected = self.selected_line_idx == Some(idx);
+                        let angle = if line.fit_kind() == CurveFitKind::Linear {
+                            format!("  θ = {:.2}°", line.world_slope_degrees())
+                        } else {
+                            String::new()
+                        };
+                        let equation = ui.selectable_label(
+                            selected,
+                            format!(
+                                "{}{}  (R² = {:.3})",
+                                line.transformed_line_equation(),
+                                angle,
+                                line.r_squared()
+                            ),
+                        );
+                        if equation.clicked() {
+                            self.selected_line_idx = (!selected).then_some(idx);
+                        }
+                        // the endpoint distance undersells curves, use the length along them
+                        let (length_label, length) = if line.fit_kind() == CurveFitKind::Linear {
+                            ("Length", line.length_in_world_space())
+                        } else {
+                            ("Arc length", line.arc_length(CURVE_SAMPLES))
+                        };
+                        let (_, circle_radius) = line.bounding_circle_world();
+                        equation.on_hover_text(format!(
+                            "{}: {:.3} units ({:.1} px)\nBounding circle radius: {:.3} units",
+                            length_label, length, screen_length, circle_radius
+                        ));
+                        if screen_length < MIN_SCREEN_LINE_LENGTH {
+                            ui.colored_label(egui::Color32::YELLOW, "⚠").on_hover_text(
+                                "This line covers very few screen pixels, the fit may be inaccurate",
+                            );
+                        }
+                        if line.requested_fit() == CurveFitKind::Auto {
+                            ui.weak(format!("Auto-selected: {}", line.fit_kind()));
+                        }
+                        if line.source == SourceKind::Loaded {
+                            ui.weak("(loaded)");
+                        }
+                        if ui
+                            .button("➕")
+                            .on_hover_text("Add points to this line, press L when done")
+                            .clicked()
+                        {
+                            extend_requested = Some(idx);
+                        }
+                        if ui
+                            .add_enabled(
+                                self.roi.is_some() && line.fit_kind() == CurveFitKind::Linear,
+                                egui::Button::new("↔"),
+                            )
+                            .on_hover_text("Extend the line to the edges of the ROI")
+                            .clicked()
+                        {
+                            snap_requested = Some(idx);
+                        }
+                        ui.checkbox(&mut line.show_band, "Show band");
+                        egui::ComboBox::from_id_source(("endpoint_style", idx))
+                            .selected_text(line.endpoint_style.display_name())
+                            .show_ui(ui, |ui| {
+                                for style in EndpointStyle::ALL {
+                                    ui.selectable_value(
+                                        &mut line.endpoint_style,
+                                        style,
+                                        style.display_name(),
+                                    );
+                                }
+                            });
+                        ui.checkbox(&mut line.show_influence, "Influence");
+                    });
+                }
+                if let Some(idx) = extend_requested {
+                    self.state = AppState::Extending(idx);
+                }
+                if let (Some(idx), Some(roi)) = (snap_requested, self.roi) {
+                    self.regression_lines[idx] = self.regression_lines[idx].snap_endpoints_to_roi(roi);
+                    self.record(UndoAction::EditLine(idx));
+                }
+                for idx in export_requested {
+                    if !self
+                        .pending_line_exports
+                        .contains(&self.regression_lines[idx].id())
+                    {
+                        self.export_line(idx);
+                    }
+                }
+                for idx in (0..keep.len()).rev().filter(|idx| !keep[*idx]) {
+                    self.regression_lines.remove(idx);
+                }
+                // keep the selection on the same lines, the ones before them may have shifted
+                let shifted = |idx: usize| idx - keep[..idx].iter().filter(|k| !**k).count();
+                self.selected_line_idx = self
+                    .selected_line_idx
+                    .filter(|&idx| keep[idx])
+                    .map(shifted);
+                self.checked_lines = self
+                    .checked_lines
+                    .iter()
+                    .filter(|&&idx| keep[idx])
+                    .map(|&idx| shifted(idx))
+                    .collect();
+                if let AppState::Extending(idx) = self.state {
+                    self.state = if keep[idx] {
+                        AppState::Extending(shifted(idx))
+                    } else {
+                        AppState::Normal
+                    };
+                }
+                let removed = (0..keep.len()).filter(|idx| !keep[*idx]).collect::<Vec<_>>();
+                match removed[..] {
+                    [] => {}
+                    [idx] => self.record(UndoAction::RemoveLine(idx)),
+                    _ => self.record(UndoAction::RemoveLines(removed.len())),
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        self.undo();
+                    }
+                    if ui
+                        .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                        .clicked()
+                    {
+                        self.redo();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Duplicate session").clicked() {
+                        self.duplicate_session = Some(self.regression_lines.clone());
+                    }
+                    if ui.button("Copy as text").clicked() {
+                        let text = self
+                            .regression_lines
+                            .iter()
+                            .map(|line| line.to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ols = ui
+                        .radio_value(&mut self.regression_mode, RegressionMode::Ols, "OLS")
+                        .on_hover_text("Minimise vertical residuals");
+                    let tls = ui
+                        .radio_value(&mut self.regression_mode, RegressionMode::Tls, "TLS")
+                        .on_hover_text("Minimise perpendicular residuals, for errors in x and y");
+                    if ols.changed() || tls.changed() {
+                        self.transform_line_segments();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let world = ui
+                        .radio_value(&mut self.fit_space, FitSpace::World, "World space")
+                        .on_hover_text("Fit the calibrated points, residuals in world units");
+                    let screen = ui
+                        .radio_value(&mut self.fit_space, FitSpace::Screen, "Screen space")
+                        .on_hover_text("Fit the clicked pixels and map the line to world space");
+                    if world.changed() || screen.changed() {
+                        self.transform_line_segments();
+                    }
+                });
+                egui::ComboBox::from_label("Fit for new lines")
+                    .selected_text(self.fit_kind.display_name())
+                    .show_ui(ui, |ui| {
+                        for kind in CurveFitKind::ALL {
+                            // polynomials of any degree share one entry
+                            let selected = std::mem::discriminant(&self.fit_kind)
+                                == std::mem::discriminant(&kind);
+                            if ui.selectable_label(selected, kind.display_name()).clicked()
+                                && !selected
+                            {
+                                self.fit_kind = kind;
+                            }
+                        }
+                    });
+                if let CurveFitKind::Polynomial(degree) = &mut self.fit_kind {
+                    ui.add(
+                        egui::Slider::new(degree, POLYNOMIAL_DEGREE.clone()).text("Degree"),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.smoothing_window, SMOOTHING_WINDOW.clone())
+                            .text("Window"),
+                    );
+                    if ui
+                        .add_enabled(
+                            self.buffered_points.len() >= self.smoothing_window,
+                            egui::Button::new("Apply smoothing"),
+                        )
+                        .on_hover_text("Moving average of the buffered points")
+                        .clicked()
+                    {
+                        self.smooth_buffered_points();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.band_alpha, 0.0..=1.0).text("Band opacity"),
+                    );
+                    let band_name = |level: Option<f32>| match level {
+                        Some(confidence) => format!("{:.0}% confidence", confidence * 100.0),
+                        None => "±1σ".to_owned(),
+                    };
+                    egui::ComboBox::from_id_source("band_kind")
+                        .selected_text(band_name(self.confidence_level))
+                        .show_ui(ui, |ui| {
+                            for level in std::iter::once(None).chain(CONFIDENCE_LEVELS.map(Some)) {
+                                ui.selectable_value(
+                                    &mut self.confidence_level,
+                                    level,
+                                    band_name(level),
+                                );
+                            }
+                        });
+                });
+
+                if ui.button("Export settings…").clicked() {
+                    self.show_export_settings = true;
+                }
+
+                // round-trip check: reload an exported world_x,world_y csv on top of the screenshot
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.load_path);
+                    if ui.button("Load CSV").clicked() && !self.load_path.is_empty() {
+                        self.send_io_request(IoRequest::LoadCsv {
+                            file_path: PathBuf::from(&self.load_path),
+                            color: RGBColor::random_color(),
+                        });
+                    }
+                });
+            });
+
+        self.draw_export_settings(ctx);
+
+        egui::Window::new("World-space plot")
+            .default_pos(egui::pos2(1000.0, 0.0))
+            .default_open(false)
+            .show(ctx, |ui| {
+                let world_points = self.regression_lines.all_world_points();
+                if !world_points.is_empty() {
+                    let (x_min, x_max) = world_points
+                        .iter()
+                        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| {
+                            (lo.min(p.x.into_inner()), hi.max(p.x.into_inner()))
+                        });
+                    let (y_min, y_max) = world_points
+                        .iter()
+                        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| {
+                            (lo.min(p.y.into_inner()), hi.max(p.y.into_inner()))
+                        });
+                    ui.label(format!(
+                        "{} points, x: [{:.3}, {:.3}], y: [{:.3}, {:.3}]",
+                        world_points.len(),
+                        x_min,
+                        x_max,
+                        y_min,
+                        y_max
+                    ));
+                }
+                ui.checkbox(&mut self.plot_residuals, "Residuals");
+                egui::CollapsingHeader::new("Residual distribution").show(ui, |ui| {
+                    egui::Grid::new("residual_distribution")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("Line");
+                            ui.label("Skewness");
+                            ui.label("Excess kurtosis");
+                            ui.end_row();
+                            for line in self.regression_lines.iter_visible() {
+                                let residuals = line.regressor().residuals_world();
+                                let skew = skewness(&residuals);
+                                let excess = kurtosis(&residuals);
+                                ui.label(&line.name);
+                                ui.monospace(format!("{:.3}", skew));
+                                ui.monospace(format!("{:.3}", excess));
+                                if skew.is_nan() || excess.is_nan() {
+                                    ui.weak("Too few points");
+                                } else if skew.abs() < NORMAL_SKEWNESS
+                                    && excess.abs() < NORMAL_EXCESS_KURTOSIS
+                                {
+                                    ui.label("Looks normal");
+                                } else {
+                                    ui.colored_label(egui::Color32::YELLOW, "Not normal");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                Plot::new("world_space_plot")
+                    .width(400.0)
+                    .height(300.0)
+                    .show(ui, |plot_ui| {
+                        for line in self.regression_lines.iter_visible() {
+                            let color: egui::Color32 = line.draw_color.into();
+                            if self.plot_residuals {
+                                let xs = line
+                                    .raw_point_coords_world()
+                                    .iter()
+                                    .map(|p| p.x.into_inner())
+                                    .collect::<Vec<_>>();
+                                let residuals = line
+                                    .sample_residuals_at_x(&xs)
+                                    .into_iter()
+                                    .map(|(x, residual)| [x as f64, residual as f64])
+                                    .collect::<PlotPoints>();
+                                plot_ui.points(
+                                    Points::new(residuals)
+                                        .color(color)
+                                        .radius(self.settings.point_radius),
+                                );
+                                continue;
+                            }
+                            let points = line
+                                .raw_point_coords_world()
+                                .iter()
+                                .map(|p| [p.x.into_inner() as f64, p.y.into_inner() as f64])
+                                .collect::<PlotPoints>();
+                            plot_ui.points(
+                                Points::new(points)
+                                    .color(color)
+                                    .radius(self.settings.point_radius),
+                            );
+                            let curve = line
+                                .world_curve_points(CURVE_SAMPLES)
+                                .iter()
+                                .map(|p| [p.x.into_inner() as f64, p.y.into_inner() as f64])
+                                .collect::<PlotPoints>();
+                            plot_ui.line(Line::new(curve).color(color));
+                        }
                     });
+            });
+
+        egui::Window::new("Intersections")
+            .default_pos(egui::pos2(1000.0, 350.0))
+            .default_open(false)
+            .show(ctx, |ui| {
+                let mut rows = self.regression_lines.intersection_rows();
+                rows.sort_by(|a, b| {
+                    let ordering = match self.intersection_sort_column {
+                        0 => a.line_a.cmp(&b.line_a),
+                        1 => a.line_b.cmp(&b.line_b),
+                        2 => a.world_pos.map(|p| p.x).cmp(&b.world_pos.map(|p| p.x)),
+                        _ => a.world_pos.map(|p| p.y).cmp(&b.world_pos.map(|p| p.y)),
+                    };
+                    if self.intersection_sort_ascending {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                });
+                egui::Grid::new("intersections_table")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (column, title) in ["Line A", "Line B", "World X", "World Y"]
+                            .iter()
+                            .enumerate()
+                        {
+                            let sorted = self.intersection_sort_column == column;
+                            let arrow = match (sorted, self.intersection_sort_ascending) {
+                                (false, _) => "",
+                                (true, true) => " ⏶",
+                                (true, false) => " ⏷",
+                            };
+                            if ui.button(format!("{}{}", title, arrow)).clicked() {
+                                if sorted {
+                                    self.intersection_sort_ascending =
+                                        !self.intersection_sort_ascending;
+                                } else {
+                                    self.intersection_sort_column = column;
+                                    self.intersection_sort_ascending = true;
+                                }
+                            }
+                        }
+                        ui.end_row();
+                        for row in &rows {
+                            ui.label(&row.line_a);
+                            ui.label(&row.line_b);
+                            match row.world_pos {
+                                Some(p) => {
+                                    ui.monospace(format!("{:.3}", p.x));
+                                    ui.monospace(format!("{:.3}", p.y));
+                                }
+                                None => {
+                                    ui.weak("Parallel");
+                                    ui.weak("Parallel");
+                                }
+                            }
+                            if ui.small_button("📋").on_hover_text("Copy row").clicked() {
+                                ui.output_mut(|o| o.copied_text = row.to_csv_row());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if rows.is_empty() {
+                    ui.weak("Needs at least two straight lines");
+                }
+                ui.checkbox(&mut self.show_intersection_labels, "Label on screenshot");
+                ui.horizontal(|ui| {
+                    if ui.button("Copy all").clicked() {
+                        let text = rows
+                            .iter()
+                            .map(ToCsvRow::to_csv_row)
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
+                    if ui
+                        .add_enabled(!rows.is_empty(), egui::Button::new("Export intersections"))
+                        .clicked()
+                    {
+                        self.send_io_request(IoRequest::SaveIntersections {
+                            rows: rows.clone(),
+                            file_path: self.save_dir.join(format!(
+                                "intersections.{}",
+                                self.export_settings.format.extension()
+                            )),
+                            settings: self.export_settings,
+                        });
+                    }
+                });
+            });
+
+        egui::Window::new("Theoretical curve")
+            .default_pos(egui::pos2(1000.0, 500.0))
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.label("Overlay y = f(x) in world coordinates, e.g. 2.5*x^2 + 3*x - 1");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.theory_enabled, "Show");
+                    ui.text_edit_singleline(&mut self.theory_formula);
+                });
+                if !self.theory_formula.trim().is_empty() {
+                    if let Err(e) = sample_formula(&self.theory_formula, (0.0, 1.0), 2) {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
                 }
-                let mut iter = keep.iter();
-                self.regression_lines.retain(|_| *iter.next().unwrap());
+            });
+
+        egui::Window::new("Colour filter")
+            .default_pos(egui::pos2(500.0, 500.0))
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.label("Only accept points whose screenshot colour matches the target");
+                ui.horizontal(|ui| {
+                    if ui.button("Pick colour").clicked() {
+                        self.state = AppState::ColorPick;
+                    }
+                    if let Some(target) = self.target_color {
+                        ui.add_enabled(false, egui::Button::new(" ".repeat(8)).fill(target));
+                    }
+                });
+                ui.add_enabled(
+                    self.target_color.is_some(),
+                    egui::Checkbox::new(&mut self.color_filter_enabled, "Filter by colour"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.color_tolerance, 0.0..=0.5).text("HSV tolerance"),
+                );
             });
 
         egui::Window::new("Transform calibration")
             .default_pos(egui::pos2(0.0, 500.0))
             .default_open(false)
             .show(ctx, |ui| {
-                ui.label("Measure two points on the screen to calibrate the transform");
+                ui.label("Measure two or more points on the screen to calibrate the transform");
                 ui.horizontal(|ui| {
                     if ui.button("Go to calibration mode").clicked() {
-                        self.gathering_state = PointGatheringState::Measurement;
+                        self.state = AppState::Measurement;
+                    }
+                    if ui.button("Add pair").clicked() {
+                        self.add_calibration_pair();
+                    }
+                    if ui.button("Save calibration").clicked() {
+                        self.pick_calibration_to_save();
+                    }
+                    if ui.button("Load calibration").clicked() {
+                        self.pick_calibration_to_load();
                     }
-                    if ui.button("Calibrate").clicked() {
+                    let can_calibrate = self.measurement_buffer.len()
+                        == self.measurement_buffer_rw_s.len()
+                        && self.measurement_buffer_rw_s.iter().all(|p| p.is_valid());
+                    if ui
+                        .add_enabled(can_calibrate, egui::Button::new("Calibrate"))
+                        .clicked()
+                    {
                         for i in 0..self.measurement_buffer.len() {
-                            // better crash on bad input than silently ignore it
+                            // the button is only enabled when every field parses
                             let point = self.measurement_buffer_rw_s[i].try_as_numeric().unwrap();
                             self.measurement_buffer_real_world[i] = point;
                         }
-                        let p1_screen = self.measurement_buffer[0];
-                        let p2_screen = self.measurement_buffer[1];
-                        let p1_rw = self.measurement_buffer_real_world[0];
-                        let p2_rw = self.measurement_buffer_real_world[1];
-                        self.current_transform = PointTransform::interpolate_from_point_pairs(
-                            (p1_screen, p1_rw),
-                            (p2_screen, p2_rw),
-                        );
-                        println!("Transform: {:?}", self.current_transform);
-                        self.gathering_state = PointGatheringState::Normal;
+                        let pairs = self
+                            .measurement_buffer
+                            .iter()
+                            .copied()
+                            .zip(self.measurement_buffer_real_world.iter().copied())
+                            .collect::<Vec<_>>();
+                        match PointTransform::fit_from_point_pairs(&pairs) {
+                            Ok(transform) => {
+                                self.apply_calibration(transform);
+                                self.calibration_pairs = Some((transform, pairs));
+                                println!("Transform: {:?}", self.target_transform);
+                                self.state = AppState::Normal;
+                            }
+                            Err(e) => {
+                                self.status_message = e.to_string();
+                            }
+                        }
                     }
                 });
+                let precision = self.settings.calibration_precision;
                 for i in 0..self.measurement_buffer.len() {
                     ui.horizontal(|ui: &mut egui::Ui| {
-                        ui.label(format!("x: {}", self.measurement_buffer[i].x));
-                        ui.label(format!("y: {}", self.measurement_buffer[i].y));
-                        ui.text_edit_singleline(&mut self.measurement_buffer_rw_s[i].x);
-                        ui.text_edit_singleline(&mut self.measurement_buffer_rw_s[i].y);
+                        let screen = PointCoordsStringy::new_formatted(
+                            self.measurement_buffer[i].x.into_inner(),
+                            self.measurement_buffer[i].y.into_inner(),
+                            precision,
+                        );
+                        ui.label(format!("x: {}", screen.x));
+                        ui.label(format!("y: {}", screen.y));
+                        ui.add(numeric_text_edit(&mut self.measurement_buffer_rw_s[i].x));
+                        ui.add(numeric_text_edit(&mut self.measurement_buffer_rw_s[i].y));
+                        ui.weak(self.measurement_buffer_rw_s[i].to_display());
                     });
                 }
+                if let Some(texture) = &self.screenshot_texture_handle {
+                    let [width, height] = texture.size();
+                    for warning in self
+                        .target_transform
+                        .plausibility_check(width as f32, height as f32)
+                    {
+                        ui.colored_label(egui::Color32::YELLOW, warning);
+                    }
+                }
+                egui::CollapsingHeader::new("Transform details").show(ui, |ui| {
+                    let transform = &self.target_transform;
+                    let scale = transform.scale();
+                    ui.label(format!(
+                        "Scale: {:.6} units per pixel ({:.3} pixels per unit)",
+                        scale,
+                        scale.recip()
+                    ));
+                    ui.label(format!(
+                        "Rotation: {:.4} rad ({:.2}°)",
+                        transform.rotation_angle_rad(),
+                        transform.rotation_angle_rad().to_degrees()
+                    ));
+                    ui.label(format!("Offset: ({:.3}, {:.3})", transform.dx, transform.dy));
+                });
+                ui.separator();
+                ui.label("Or hover over axis ticks and press H (horizontal) or V (vertical)");
+                ui.horizontal(|ui| {
+                    if ui.button("Go to axis tick mode").clicked() {
+                        self.state = AppState::TickCalibration;
+                    }
+                    ui.label(format!(
+                        "{}/{} horizontal, {}/{} vertical",
+                        self.horizontal_ticks.len(),
+                        NUM_TICKS_PER_AXIS,
+                        self.vertical_ticks.len(),
+                        NUM_TICKS_PER_AXIS
+                    ));
+                });
+                ui.horizontal(|ui| {
+                    let enabled = self.screenshot_image.is_some() && self.pending_tick.is_none();
+                    if ui
+                        .add_enabled(enabled, egui::Button::new("Detect horizontal ticks"))
+                        .clicked()
+                    {
+                        self.detect_ticks(Axis::Horizontal);
+                    }
+                    if ui
+                        .add_enabled(enabled, egui::Button::new("Detect vertical ticks"))
+                        .clicked()
+                    {
+                        self.detect_ticks(Axis::Vertical);
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Finds the tick spacing in the screenshot (or ROI), only the values need typing in",
+                );
+                let history = self.transform_history.past();
+                if history.len() >= 2 {
+                    let blend = ui.add(
+                        egui::Slider::new(&mut self.transform_blend, 0.0..=1.0)
+                            .text("Blend")
+                            .clamp_to_range(true),
+                    );
+                    if blend.changed() {
+                        self.set_transform(PointTransform::interpolate_between(
+                            history[history.len() - 2],
+                            history[history.len() - 1],
+                            self.transform_blend,
+                        ));
+                    }
+                }
+                ui.add(
+                    egui::Slider::new(&mut self.settings.transform_animation_secs, 0.0..=2.0)
+                        .text("Animation")
+                        .suffix(" s"),
+                );
+                ui.collapsing("Scale and offset", |ui| {
+                    let [sx, sy, ox, oy] = &mut self.manual_scale;
+                    egui::Grid::new("manual_scale").show(ui, |ui| {
+                        ui.label("x = ");
+                        ui.add(egui::DragValue::new(sx).speed(0.01));
+                        ui.label("* screen_x +");
+                        ui.add(egui::DragValue::new(ox));
+                        ui.end_row();
+                        ui.label("y = ");
+                        ui.add(egui::DragValue::new(sy).speed(0.01));
+                        ui.label("* screen_y +");
+                        ui.add(egui::DragValue::new(oy));
+                        ui.end_row();
+                    });
+                    if *sy != -*sx {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "Both axes share one scale, the y scale is taken as -sx",
+                        );
+                    }
+                    if ui.button("Apply").clicked() {
+                        let [sx, sy, ox, oy] = self.manual_scale;
+                        self.apply_calibration(PointTransform::from_scale_and_offset(
+                            sx, sy, ox, oy,
+                        ));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            self.transform_history.can_undo(),
+                            egui::Button::new("Undo calibration"),
+                        )
+                        .clicked()
+                    {
+                        self.undo_calibration();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.transform_history.can_redo(),
+                            egui::Button::new("Redo calibration"),
+                        )
+                        .clicked()
+                    {
+                        self.redo_calibration();
+                    }
+                });
+                ui.collapsing("Transform matrix", |ui| {
+                    ui.label(format!("det ≈ {:.4}", self.target_transform.determinant()));
+                    egui::Grid::new("transform_matrix")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for row in self.target_transform.as_matrix_4x4() {
+                                for value in row {
+                                    ui.monospace(format!("{:.6}", value));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
             });
+
+        self.draw_tick_popup(ctx);
+        self.draw_duplicate_session(ctx);
+        self.draw_monitor_select(ctx);
     }
 }
 
+#[derive(Parser)]
+#[command(version, about = "Digitise plots from screenshots")]
+struct Args {
+    #[arg(
+        long,
+        value_name = "JSON",
+        help = "Start calibrated with a transform side-car instead of the identity"
+    )]
+    transform: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Directory exports are saved to [default: ./exported_lines]"
+    )]
+    output_dir: Option<PathBuf>,
+}
+
 fn main() {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    let args = Args::parse();
+    if let Some(dir) = &args.output_dir {
+        if let Err(e) = ensure_writable_dir(dir) {
+            eprintln!(
+                "Can't write to the output directory {}: {}",
+                dir.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    }
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_fullscreen(true),
         ..Default::default()
     };
     eframe::run_native(
-        "My egui App",
+        WINDOW_TITLE,
         options,
-        Box::new(|_c| Ok(Box::<App>::default())),
+        Box::new(move |_c| {
+            let mut app = App::default();
+            if let Some(dir) = &args.output_dir {
+                app.save_dir = dir.clone();
+            }
+            if let Some(path) = &args.transform {
+                app.load_transform(path);
+            }
+            Ok(Box::new(app))
+        }),
     )
     .unwrap();
 }
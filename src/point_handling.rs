@@ -1,14 +1,27 @@
-use faer::{self, mat, solvers::SpSolver};
+use crate::error::SciShotError;
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Value};
+use faer::{
+    self, mat,
+    solvers::{SpSolver, SpSolverLstsq},
+    Mat,
+};
+use indexmap::IndexSet;
 use ordered_float::OrderedFloat;
-pub type UniquePointBuf = HashSet<PointCoords>;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+// keeps the order in which points were placed, e.g. for the replay
+pub type UniquePointBuf = IndexSet<PointCoords>;
 use num_traits::Float;
 use std::{
-    collections::HashSet,
+    collections::HashMap,
     fmt::Display,
-    ops::{Add, Sub},
+    ops::{Add, Mul, Sub},
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointTransform {
     pub alpha: f32, // Cos theta
     pub beta: f32,  // Sin theta
@@ -17,6 +30,7 @@ pub struct PointTransform {
 }
 
 #[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointCoords {
     pub x: OrderedFloat<f32>,
     pub y: OrderedFloat<f32>,
@@ -28,26 +42,222 @@ pub struct PointCoordsStringy {
     pub y: String,
 }
 
+// a labelled tick on one of the graph axes, `value` is the axis reading at `screen_pos`
+#[derive(Clone, Copy, Debug)]
+pub struct AxisTick {
+    pub screen_pos: PointCoords,
+    pub value: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeasurementKind {
+    Distance,
+    Angle,
+}
+
+#[derive(Clone, Debug)]
+pub struct MeasurementResult {
+    pub kind: MeasurementKind,
+    pub world_value: f32,
+    pub label: String,
+    pub screen_pos: PointCoords,
+}
+
+// row of the intersections table, `world_pos` is None for parallel lines
+#[derive(Clone, Debug)]
+pub struct IntersectionRow {
+    pub line_a: String,
+    pub line_b: String,
+    pub world_pos: Option<PointCoords>,
+}
+
 pub trait Transformable {
+    #[must_use]
     fn transform(&self, transform: &PointTransform) -> Self;
 }
 
-struct RegressionLineSegment {
+// Summary positions of a point set, both None for an empty set
+pub trait PointStats {
+    // arithmetic mean of the points
+    #[must_use]
+    fn mean_position(&self) -> Option<PointCoords>;
+    // weighted mean, points without a weight count as weightless
+    #[must_use]
+    fn center_of_mass(&self, weights: &HashMap<PointCoords, f32>) -> Option<PointCoords>;
+    // smallest circle containing every point, as (center, radius)
+    #[must_use]
+    fn min_enclosing_circle(&self) -> Option<(PointCoords, f32)>;
+}
+
+pub trait Deduplicate {
+    // merges points that are within `eps` of each other into their mean
+    #[must_use]
+    fn remove_duplicates_within(&self, eps: f32) -> Self;
+}
+
+pub trait ToFaerMatrix {
+    // one `[x, y]` row per point, with a third column of ones when `include_bias` is set
+    #[must_use]
+    fn to_matrix_faer(&self, include_bias: bool) -> Mat<f32>;
+}
+
+pub trait ToCsvRow {
+    #[must_use]
+    fn to_csv_row(&self) -> String;
+}
+
+// field-wise tolerance of `PointTransform`'s `==`
+static TRANSFORM_EQ_EPS: f32 = 1e-5;
+
+// transforms with a smaller determinant squash the screen into (almost) a single point
+static MIN_DETERMINANT: f32 = 1e-6;
+// relative difference allowed between the x and y scales implied by axis ticks, a similarity
+// can't map axes with different scales so anything beyond clicking noise is rejected
+static AXIS_SCALE_TOLERANCE: f32 = 0.02;
+
+// Two-parameter models that become a straight line after taking logarithms of one or
+// both axes, so all of them can be fitted with the same OLS routine. Polynomials of the
+// given degree are the exception, they are solved from their normal equations instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CurveFitKind {
+    Linear,
+    Exponential,
+    PowerLaw,
+    Logarithmic,
+    Polynomial(usize),
+    Auto,
+}
+
+// OLS minimises the vertical residuals, TLS the perpendicular ones, which suits data
+// with measurement error on both axes. TLS only applies to linear fits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegressionMode {
+    Ols,
+    Tls,
+}
+
+// Where linear fits are computed. World fits the calibrated points, so the residuals are in
+// world units. Screen fits the clicked pixels once and maps that line into world space, it
+// matches the drawn line exactly but weighs the residuals in pixels. Non-linear fits are
+// always made in world space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FitSpace {
+    Screen,
+    World,
+}
+
+// Auto prefers a simpler model (earlier in the list) unless a later one beats it by more than this
+static AUTO_FIT_R_SQUARED_TOLERANCE: f32 = 0.01;
+
+// resamples used for the band of non-linear fits; the seed is fixed so the band
+// doesn't flicker when the line is refitted every frame
+static BOOTSTRAP_RESAMPLES: usize = 200;
+static BOOTSTRAP_SEED: u64 = 0;
+
+// fixed for the same reason, the same clicks always keep the same inliers
+static RANSAC_SEED: u64 = 0;
+
+// two-sided normal quantiles of the confidence levels `t_critical` knows
+static CONFIDENCE_QUANTILES: [(f32, f32); 3] = [(0.90, 1.645), (0.95, 1.960), (0.99, 2.576)];
+
+#[derive(Clone, Debug)]
+struct CurveFit {
+    kind: CurveFitKind,
+    slope: f32,
+    intercept: f32,
+    // lowest order first, only set for polynomial fits
+    coefficients: Vec<f32>,
+    r_squared: f32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegressionLineSegment {
+    // slope and intercept of the linearised model, see `CurveFitKind::linearize`
     transformed_slope: f32,
     transformed_intercept: f32,
+    requested_fit: CurveFitKind,
+    regression_mode: RegressionMode,
+    fit_space: FitSpace,
+    // never `Auto`, this is what the requested fit resolved to
+    fit_kind: CurveFitKind,
+    // lowest order first, only set for polynomial fits
+    coefficients: Vec<f32>,
+    r_squared: f32,
+    orientation: LineOrientation,
+    // world x of a vertical line, unused otherwise
+    constant_x: f32,
     // We save the transform so we can later export the struct to a file
     transform: PointTransform,
     screen_points: UniquePointBuf,
+    // points rejected by `new_ransac`, not part of the fit
+    outliers: UniquePointBuf,
+}
+
+// how the ends of a drawn line are decorated, the arrow points from left to right
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EndpointStyle {
+    None,
+    Circle,
+    Arrow,
+    Square,
+}
+
+// Lines whose points share (almost) the same x can't be fitted as y = f(x), since the
+// denominator of the OLS slope vanishes. They are kept as x = const instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineOrientation {
+    Horizontal,
+    Vertical,
+    General,
 }
 
+// ids handed out to lines, never reused so that a removed line's id can't match a new one
+static NEXT_LINE_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_line_id() -> u64 {
+    NEXT_LINE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// a spread along one axis below this fraction of the other makes the line axis-aligned
+static ORIENTATION_TOLERANCE: f32 = 1e-3;
+// lines whose world-space angles differ by less than this many degrees don't intersect
+static PARALLEL_ANGLE_TOLERANCE_DEG: f32 = 1e-4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SourceKind {
+    Digitised,
+    Loaded,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScreenLineSegment {
     regressor: RegressionLineSegment,
+    // stable for the life of the line and shared by its clones, unlike its index in a
+    // `LineSet`. Not saved, lines get a fresh one when they are loaded.
+    #[cfg_attr(feature = "serde", serde(skip, default = "next_line_id"))]
+    id: u64,
+    // unique within a `LineSet`, which fills it in when the line is added
+    pub name: String,
+    pub visible: bool,
     pub rightmost_pt: PointCoords,
     pub leftmost_pt: PointCoords,
     pub draw_color: RGBColor,
+    pub source: SourceKind,
+    pub show_influence: bool,
+    pub show_band: bool,
+    pub endpoint_style: EndpointStyle,
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RGBColor {
     pub r: u8,
     pub g: u8,
@@ -55,6 +265,8 @@ pub struct RGBColor {
 }
 
 impl PointTransform {
+    #[must_use]
+    #[inline]
     pub fn new(alpha: f32, beta: f32, dx: f32, dy: f32) -> Self {
         PointTransform {
             alpha,
@@ -63,6 +275,8 @@ impl PointTransform {
             dy,
         }
     }
+    #[must_use]
+    #[inline]
     pub const fn identity() -> Self {
         PointTransform {
             alpha: 1.0,
@@ -71,41 +285,364 @@ impl PointTransform {
             dy: 0.0,
         }
     }
+    // For users who know their pixels-per-unit: world_x = sx * screen_x + ox and
+    // world_y = sy * screen_y + oy. Only exact for pixel-aligned axes, and since the
+    // transform has a single scale and flips y, `_sy` is taken to be -sx; a graph whose
+    // axes have different scales still needs a proper calibration.
+    #[must_use]
+    pub fn from_scale_and_offset(sx: f32, _sy: f32, ox: f32, oy: f32) -> Self {
+        PointTransform::new(sx, 0.0, ox, oy)
+    }
+    // every field within `eps` of `other`'s
+    #[must_use]
+    #[inline]
+    pub fn near_equals(&self, other: &PointTransform, eps: f32) -> bool {
+        (self.alpha - other.alpha).abs() <= eps
+            && (self.beta - other.beta).abs() <= eps
+            && (self.dx - other.dx).abs() <= eps
+            && (self.dy - other.dy).abs() <= eps
+    }
+    // true when the transform only shifts points, i.e. screen pixels are already world units
+    #[must_use]
+    #[inline]
+    pub fn translates_only(&self, eps: f32) -> bool {
+        (self.alpha - 1.0).abs() <= eps && self.beta.abs() <= eps
+    }
+    // homogeneous form of the transform, row-major
+    #[must_use]
+    pub fn as_matrix_4x4(&self) -> [[f32; 4]; 4] {
+        [
+            [self.alpha, -self.beta, self.dx, 0.0],
+            [self.beta, self.alpha, self.dy, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+    // of the rotation-scale part, i.e. the squared scale factor
+    #[must_use]
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.alpha * self.alpha + self.beta * self.beta
+    }
+    // world units per screen pixel
+    #[must_use]
+    #[inline]
+    pub fn scale(&self) -> f32 {
+        (self.alpha * self.alpha + self.beta * self.beta).sqrt()
+    }
+    // counter-clockwise, in (-pi, pi]
+    #[must_use]
+    #[inline]
+    pub fn rotation_angle_rad(&self) -> f32 {
+        self.beta.atan2(self.alpha)
+    }
+    // Human-readable warnings for calibrations that are technically valid but most likely
+    // a typo, e.g. world values of 1 and 2 for points a thousand pixels apart
+    #[must_use]
+    pub fn plausibility_check(&self, screen_width: f32, screen_height: f32) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let scale = self.scale();
+        if scale > screen_width.max(screen_height) {
+            warnings.push(format!(
+                "Scale of {:.3} units per pixel is larger than the screen",
+                scale
+            ));
+        } else if scale < 0.001 {
+            warnings.push(format!("Scale of {:.2e} units per pixel is tiny", scale));
+        }
+        let rotation = self.rotation_angle_rad().to_degrees();
+        if rotation.abs() > 45.0 {
+            warnings.push(format!(
+                "Rotated by {:.1}°, is the graph upright?",
+                rotation
+            ));
+        }
+        if self.dx.abs() > 5.0 * screen_width || self.dy.abs() > 5.0 * screen_height {
+            warnings.push(format!(
+                "Offset ({:.1}, {:.1}) is far outside of the screen",
+                self.dx, self.dy
+            ));
+        }
+        warnings
+    }
+    // maps world coordinates back to screen coordinates
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        let scale = self.alpha * self.alpha + self.beta * self.beta;
+        PointTransform::new(
+            self.alpha / scale,
+            self.beta / scale,
+            -(self.alpha * self.dx + self.beta * self.dy) / scale,
+            (self.alpha * self.dy - self.beta * self.dx) / scale,
+        )
+    }
+
+    // `inverse`, or None when the transform squashes everything into (almost) one point
+    #[must_use]
+    pub fn invert(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        (determinant >= MIN_DETERMINANT).then(|| self.inverse())
+    }
+
+    // maps a single world point back to the screen, shorthand for `p.transform(&t.inverse())`
+    #[must_use]
+    #[inline]
+    pub fn apply_inverse(&self, p: PointCoords) -> PointCoords {
+        p.transform(&self.inverse())
+    }
+
     pub fn interpolate_from_point_pairs(
-        (p1_screen, p1_rw): (PointCoords, PointCoords),
-        (p2_screen, p2_rw): (PointCoords, PointCoords),
-    ) -> Self {
-        let mtx = mat![
-            [p1_screen.x.into_inner(), p1_screen.y.into_inner(), 1.0, 0.0],
-            [
-                -p1_screen.y.into_inner(),
-                p1_screen.x.into_inner(),
-                0.0,
-                1.0
-            ],
-            [p2_screen.x.into_inner(), p2_screen.y.into_inner(), 1.0, 0.0],
-            [
-                -p2_screen.y.into_inner(),
-                p2_screen.x.into_inner(),
-                0.0,
-                1.0
-            ],
-        ];
-        let rhs = mat![[
-            p1_rw.x.into_inner(),
-            p1_rw.y.into_inner(),
-            p2_rw.x.into_inner(),
-            p2_rw.y.into_inner()
-        ]];
-        let lu = mtx.full_piv_lu();
-        let x = lu.solve(rhs.transpose());
+        p1: (PointCoords, PointCoords),
+        p2: (PointCoords, PointCoords),
+    ) -> Result<Self, SciShotError> {
+        Self::fit_from_point_pairs(&[p1, p2])
+    }
+    // Least-squares transform through (screen, world) pairs, exact for two of them. Every
+    // pair adds two rows to the 2N×4 system in (alpha, beta, dx, dy), solved by QR.
+    pub fn fit_from_point_pairs(
+        pairs: &[(PointCoords, PointCoords)],
+    ) -> Result<Self, SciShotError> {
+        if pairs.len() < 2 {
+            return Err(SciShotError::CalibrationError(format!(
+                "need at least two calibration points, got {}",
+                pairs.len()
+            )));
+        }
+        // the system below is singular when all screen points coincide
+        let first = pairs[0].0;
+        if pairs
+            .iter()
+            .all(|(screen, _)| first.distance_to(screen) <= f32::EPSILON)
+        {
+            return Err(SciShotError::CalibrationError(
+                "the calibration points are on the same screen position".to_owned(),
+            ));
+        }
+        let mtx = Mat::<f32>::from_fn(2 * pairs.len(), 4, |i, j| {
+            let screen = pairs[i / 2].0;
+            let (x, y) = (screen.x.into_inner(), screen.y.into_inner());
+            let row = if i % 2 == 0 {
+                [x, y, 1.0, 0.0]
+            } else {
+                [-y, x, 0.0, 1.0]
+            };
+            row[j]
+        });
+        let rhs = Mat::<f32>::from_fn(2 * pairs.len(), 1, |i, _| {
+            let world = pairs[i / 2].1;
+            if i % 2 == 0 {
+                world.x.into_inner()
+            } else {
+                world.y.into_inner()
+            }
+        });
+        let x = mtx.qr().solve_lstsq(&rhs);
+
+        let transform = PointTransform::new(x[(0, 0)], x[(1, 0)], x[(2, 0)], x[(3, 0)]);
+        let determinant = transform.determinant();
+        if determinant.is_nan() || determinant < MIN_DETERMINANT {
+            return Err(SciShotError::CalibrationError(format!(
+                "degenerate transform (det ≈ {:.4}), are the world coordinates distinct?",
+                determinant
+            )));
+        }
+        Ok(transform)
+    }
+    // field-wise linear blend, `alpha` is clamped to [0, 1] where 0 gives `t1` and 1 gives `t2`
+    #[must_use]
+    pub fn interpolate_between(t1: Self, t2: Self, alpha: f32) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let lerp = |a: f32, b: f32| (1.0 - alpha) * a + alpha * b;
+        PointTransform::new(
+            lerp(t1.alpha, t2.alpha),
+            lerp(t1.beta, t2.beta),
+            lerp(t1.dx, t2.dx),
+            lerp(t1.dy, t2.dy),
+        )
+    }
+    // Calibrates from two ticks on each axis by pairing the screen x of a horizontal tick
+    // with the screen y of a vertical tick, so the calibration points always sit on the
+    // axis grid instead of wherever the user happened to click. This assumes the axes are
+    // aligned with the screen and, as the transform is a similarity, that both axes share
+    // a scale. Fails when the two ticks of an axis sit on the same screen position, or when
+    // the tick spacings imply different (or differently signed) scales on the two axes.
+    pub fn from_axis_ticks(
+        horizontal: [AxisTick; 2],
+        vertical: [AxisTick; 2],
+    ) -> Result<Self, SciShotError> {
+        let dx_screen = (horizontal[1].screen_pos.x - horizontal[0].screen_pos.x).into_inner();
+        let dy_screen = (vertical[1].screen_pos.y - vertical[0].screen_pos.y).into_inner();
+        if dx_screen.abs() <= f32::EPSILON || dy_screen.abs() <= f32::EPSILON {
+            return Err(SciShotError::CalibrationError(
+                "ticks on the same axis must not overlap".to_owned(),
+            ));
+        }
+        // world units per pixel, screen y points down so a y axis growing upwards is positive
+        let x_scale = (horizontal[1].value - horizontal[0].value) / dx_screen;
+        let y_scale = -(vertical[1].value - vertical[0].value) / dy_screen;
+        if (x_scale - y_scale).abs() > AXIS_SCALE_TOLERANCE * x_scale.abs().max(y_scale.abs()) {
+            return Err(SciShotError::CalibrationError(format!(
+                "the axes have different scales ({:.4} and {:.4} units per pixel), \
+                 calibrate from measured points instead",
+                x_scale, y_scale
+            )));
+        }
+        let pair = |h: AxisTick, v: AxisTick| {
+            (
+                PointCoords::new(h.screen_pos.x.into_inner(), v.screen_pos.y.into_inner()),
+                PointCoords::new(h.value, v.value),
+            )
+        };
+        Self::interpolate_from_point_pairs(
+            pair(horizontal[0], vertical[0]),
+            pair(horizontal[1], vertical[1]),
+        )
+    }
+}
+
+// A calibration file keeps the (screen, world) pairs next to the transform, so they can be
+// checked or edited in a text editor. Loading refits the transform from two or more pairs,
+// edits to them take effect, without pairs the stored transform is used as is.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CalibrationFile {
+    transform: PointTransform,
+    #[serde(default)]
+    pairs: Vec<CalibrationPair>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CalibrationPair {
+    screen: PointCoords,
+    world: PointCoords,
+}
+
+#[cfg(feature = "serde")]
+impl PointTransform {
+    pub fn save_to_file(
+        &self,
+        path: &Path,
+        pairs: &[(PointCoords, PointCoords)],
+    ) -> Result<(), SciShotError> {
+        let file = CalibrationFile {
+            transform: *self,
+            pairs: pairs
+                .iter()
+                .map(|&(screen, world)| CalibrationPair { screen, world })
+                .collect(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| SciShotError::ParseError(e.to_string()))?;
+        crate::io_utils::write_atomic(path, json.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_from_file(
+        path: &Path,
+    ) -> Result<(Self, Vec<(PointCoords, PointCoords)>), SciShotError> {
+        let json = std::fs::read_to_string(path)?;
+        let file: CalibrationFile =
+            serde_json::from_str(&json).map_err(|e| SciShotError::ParseError(e.to_string()))?;
+        let pairs = file
+            .pairs
+            .into_iter()
+            .map(|pair| (pair.screen, pair.world))
+            .collect::<Vec<_>>();
+        let transform = if pairs.len() >= 2 {
+            PointTransform::fit_from_point_pairs(&pairs)?
+        } else {
+            file.transform
+        };
+        Ok((transform, pairs))
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+impl PointTransform {
+    pub fn save_to_file(
+        &self,
+        _path: &Path,
+        _pairs: &[(PointCoords, PointCoords)],
+    ) -> Result<(), SciShotError> {
+        Err(calibration_file_unsupported())
+    }
+
+    pub fn load_from_file(
+        _path: &Path,
+    ) -> Result<(Self, Vec<(PointCoords, PointCoords)>), SciShotError> {
+        Err(calibration_file_unsupported())
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn calibration_file_unsupported() -> SciShotError {
+    SciShotError::IoError(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "calibration files need the serde feature",
+    ))
+}
+
+#[cfg(feature = "nalgebra")]
+impl PointTransform {
+    // The rigid part of the transform, for nalgebra based code. An isometry can't scale,
+    // so the scale (sqrt of the determinant) is dropped and only the rotation and the
+    // offset are kept. The y flip of screen coordinates isn't part of it either, the
+    // isometry acts on y-up points.
+    #[must_use]
+    pub fn as_nalgebra_isometry(&self) -> nalgebra::Isometry2<f32> {
+        nalgebra::Isometry2::from_parts(
+            nalgebra::Translation2::new(self.dx, self.dy),
+            nalgebra::UnitComplex::new(self.beta.atan2(self.alpha)),
+        )
+    }
+}
+
+// unit scale, the inverse of `as_nalgebra_isometry` for transforms that don't scale
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Isometry2<f32>> for PointTransform {
+    fn from(isometry: nalgebra::Isometry2<f32>) -> Self {
+        PointTransform::new(
+            isometry.rotation.cos_angle(),
+            isometry.rotation.sin_angle(),
+            isometry.translation.x,
+            isometry.translation.y,
+        )
+    }
+}
+
+// `a * b` applies `a`, then `b` to the world coordinates, e.g. a pixels to mm calibration
+// followed by a mm to inch conversion. Only `a` flips screen y, `b` is a plain similarity
+// in world space, so its rotation-scale and offset compose with `a`'s like complex numbers.
+impl Mul for PointTransform {
+    type Output = PointTransform;
+
+    fn mul(self, rhs: PointTransform) -> PointTransform {
+        PointTransform::new(
+            rhs.alpha * self.alpha - rhs.beta * self.beta,
+            rhs.beta * self.alpha + rhs.alpha * self.beta,
+            rhs.alpha * self.dx - rhs.beta * self.dy + rhs.dx,
+            rhs.beta * self.dx + rhs.alpha * self.dy + rhs.dy,
+        )
+    }
+}
 
-        PointTransform::new(x[(0, 0)], x[(1, 0)], x[(2, 0)], x[(3, 0)])
+// approximate, transforms that went through a calibration rarely match bit for bit
+impl PartialEq for PointTransform {
+    fn eq(&self, other: &Self) -> bool {
+        self.near_equals(other, TRANSFORM_EQ_EPS)
     }
 }
 
 impl Transformable for PointCoords {
     fn transform(&self, transform: &PointTransform) -> Self {
+        // skip the matrix multiply for the common uncalibrated/pixel-unit case
+        if transform.translates_only(f32::EPSILON) {
+            return PointCoords::new(
+                self.x.into_inner() + transform.dx,
+                -self.y.into_inner() + transform.dy,
+            );
+        }
         let m = mat![
             [transform.alpha, -transform.beta],
             [transform.beta, transform.alpha],
@@ -128,13 +665,226 @@ impl Transformable for UniquePointBuf {
     }
 }
 
+impl PointStats for UniquePointBuf {
+    fn mean_position(&self) -> Option<PointCoords> {
+        if self.is_empty() {
+            return None;
+        }
+        let n = self.len() as f32;
+        let (sum_x, sum_y) = self.iter().fold((0.0, 0.0), |(x, y), p| {
+            (x + p.x.into_inner(), y + p.y.into_inner())
+        });
+        Some(PointCoords::new(sum_x / n, sum_y / n))
+    }
+
+    fn center_of_mass(&self, weights: &HashMap<PointCoords, f32>) -> Option<PointCoords> {
+        let (sum_w, sum_x, sum_y) = self.iter().fold((0.0, 0.0, 0.0), |(w, x, y), p| {
+            let weight = weights.get(p).copied().unwrap_or(0.0);
+            (
+                w + weight,
+                x + weight * p.x.into_inner(),
+                y + weight * p.y.into_inner(),
+            )
+        });
+        if sum_w <= 0.0 {
+            return None;
+        }
+        Some(PointCoords::new(sum_x / sum_w, sum_y / sum_w))
+    }
+
+    // incremental Welzl: whenever a point falls outside of the current circle it has to
+    // be on the boundary of the new one. O(n³) in the worst case, fine for clicked points.
+    fn min_enclosing_circle(&self) -> Option<(PointCoords, f32)> {
+        let points = self.iter().copied().collect::<Vec<_>>();
+        let contains = |(center, radius): (PointCoords, f32), p: &PointCoords| {
+            center.distance_to(p) <= radius * (1.0 + f32::EPSILON) + f32::EPSILON
+        };
+        let mut circle = (*points.first()?, 0.0);
+        for i in 1..points.len() {
+            if contains(circle, &points[i]) {
+                continue;
+            }
+            circle = circle_from_diameter(points[i], points[0]);
+            for j in 1..i {
+                if contains(circle, &points[j]) {
+                    continue;
+                }
+                circle = circle_from_diameter(points[i], points[j]);
+                for k in 0..j {
+                    if !contains(circle, &points[k]) {
+                        circle = circumcircle(points[i], points[j], points[k]);
+                    }
+                }
+            }
+        }
+        Some(circle)
+    }
+}
+
+fn circle_from_diameter(a: PointCoords, b: PointCoords) -> (PointCoords, f32) {
+    let center = PointCoords::new(
+        (a.x.into_inner() + b.x.into_inner()) / 2.0,
+        (a.y.into_inner() + b.y.into_inner()) / 2.0,
+    );
+    (center, a.distance_to(&b) / 2.0)
+}
+
+// circle through all three points; collinear points have none, the circle over the two
+// farthest apart covers them instead
+fn circumcircle(a: PointCoords, b: PointCoords, c: PointCoords) -> (PointCoords, f32) {
+    let (bx, by) = ((b - a).x.into_inner(), (b - a).y.into_inner());
+    let (cx, cy) = ((c - a).x.into_inner(), (c - a).y.into_inner());
+    let d = 2.0 * (bx * cy - by * cx);
+    if d.abs() <= f32::EPSILON {
+        return [(a, b), (a, c), (b, c)]
+            .into_iter()
+            .map(|(p, q)| circle_from_diameter(p, q))
+            .max_by(|(_, r1), (_, r2)| r1.total_cmp(r2))
+            .unwrap();
+    }
+    let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+    let center = PointCoords::new(
+        a.x.into_inner() + (cy * b2 - by * c2) / d,
+        a.y.into_inner() + (bx * c2 - cx * b2) / d,
+    );
+    (center, center.distance_to(&a))
+}
+
+impl ToFaerMatrix for UniquePointBuf {
+    fn to_matrix_faer(&self, include_bias: bool) -> Mat<f32> {
+        let cols = if include_bias { 3 } else { 2 };
+        Mat::from_fn(self.len(), cols, |i, j| match j {
+            0 => self[i].x.into_inner(),
+            1 => self[i].y.into_inner(),
+            _ => 1.0,
+        })
+    }
+}
+
+impl Deduplicate for UniquePointBuf {
+    // greedy O(n²) clustering around the first unclaimed point, fine for a few hundred
+    // hand-placed points; clusters keep the position of their first point in the order
+    fn remove_duplicates_within(&self, eps: f32) -> Self {
+        let points = self.iter().collect::<Vec<_>>();
+        let mut claimed = vec![false; points.len()];
+        let mut deduplicated = UniquePointBuf::with_capacity(points.len());
+        for i in 0..points.len() {
+            if claimed[i] {
+                continue;
+            }
+            let cluster = (i..points.len())
+                .filter(|&j| !claimed[j] && points[i].distance_to(points[j]) <= eps)
+                .collect::<Vec<_>>();
+            for &j in &cluster {
+                claimed[j] = true;
+            }
+            let cluster = cluster
+                .iter()
+                .map(|&j| *points[j])
+                .collect::<UniquePointBuf>();
+            // never None, the cluster contains at least point i
+            deduplicated.extend(cluster.mean_position());
+        }
+        deduplicated
+    }
+}
+
 impl PointCoords {
+    #[must_use]
+    #[inline]
     pub fn new(x: f32, y: f32) -> Self {
         PointCoords {
             x: OrderedFloat(x),
             y: OrderedFloat(y),
         }
     }
+
+    #[must_use]
+    #[inline]
+    pub fn approx_eq(&self, other: &PointCoords, eps: f32) -> bool {
+        (self.x - other.x).abs() <= OrderedFloat(eps)
+            && (self.y - other.y).abs() <= OrderedFloat(eps)
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn distance_to(&self, other: &PointCoords) -> f32 {
+        (self.x - other.x)
+            .into_inner()
+            .hypot((self.y - other.y).into_inner())
+    }
+
+    // componentwise clamp into the (min, max) box
+    #[must_use]
+    #[inline]
+    pub fn clamp(&self, min: PointCoords, max: PointCoords) -> PointCoords {
+        PointCoords {
+            x: Ord::min(Ord::max(self.x, min.x), max.x),
+            y: Ord::min(Ord::max(self.y, min.y), max.y),
+        }
+    }
+
+    // polar angle in degrees, counter-clockwise from the positive x axis
+    #[must_use]
+    #[inline]
+    pub fn angle_to_origin(&self) -> f32 {
+        self.y.into_inner().atan2(self.x.into_inner()).to_degrees()
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn from_polar(r: f32, theta_deg: f32) -> PointCoords {
+        let (sin, cos) = theta_deg.to_radians().sin_cos();
+        PointCoords::new(r * cos, r * sin)
+    }
+
+    // foot of the perpendicular from the point to y = slope * x + intercept
+    #[must_use]
+    pub fn project_onto_line(&self, slope: f32, intercept: f32) -> PointCoords {
+        let (x, y) = (self.x.into_inner(), self.y.into_inner());
+        let foot_x = (x + slope * (y - intercept)) / (1.0 + slope * slope);
+        PointCoords::new(foot_x, slope * foot_x + intercept)
+    }
+}
+
+impl ToCsvRow for PointCoords {
+    fn to_csv_row(&self) -> String {
+        format!("{},{}", self.x, self.y)
+    }
+}
+
+// e.g. a screen point next to its world point
+impl<A: ToCsvRow, B: ToCsvRow> ToCsvRow for (A, B) {
+    fn to_csv_row(&self) -> String {
+        format!("{},{}", self.0.to_csv_row(), self.1.to_csv_row())
+    }
+}
+
+impl ToCsvRow for MeasurementResult {
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.kind.as_str(),
+            self.world_value,
+            self.screen_pos.x,
+            self.screen_pos.y
+        )
+    }
+}
+
+impl ToCsvRow for IntersectionRow {
+    fn to_csv_row(&self) -> String {
+        match self.world_pos {
+            Some(p) => format!("{},{},{},{}", self.line_a, self.line_b, p.x, p.y),
+            None => format!("{},{},parallel,parallel", self.line_a, self.line_b),
+        }
+    }
 }
 
 impl Sub for PointCoords {
@@ -158,6 +908,7 @@ impl Add for PointCoords {
 }
 
 impl PointCoordsStringy {
+    #[must_use]
     pub fn new_numeric(x: f32, y: f32) -> Self {
         PointCoordsStringy {
             x: x.to_string(),
@@ -165,14 +916,304 @@ impl PointCoordsStringy {
         }
     }
 
-    pub fn try_as_numeric(&self) -> Option<PointCoords> {
-        let x = self.x.parse::<f32>().ok()?;
-        let y = self.y.parse::<f32>().ok()?;
-        Some(PointCoords::new(x, y))
+    // like `new_numeric`, with `precision` decimals instead of every digit of the f32
+    #[must_use]
+    pub fn new_formatted(x: f32, y: f32, precision: usize) -> Self {
+        PointCoordsStringy {
+            x: format!("{:.prec$}", x, prec = precision),
+            y: format!("{:.prec$}", y, prec = precision),
+        }
+    }
+
+    pub fn try_as_numeric(&self) -> Result<PointCoords, SciShotError> {
+        let parse = |text: &str| {
+            text.trim()
+                .parse::<f32>()
+                .map_err(|_| SciShotError::ParseError(format!("'{}' is not a number", text)))
+        };
+        Ok(PointCoords::new(parse(&self.x)?, parse(&self.y)?))
+    }
+
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.try_as_numeric().is_ok()
+    }
+
+    #[must_use]
+    pub fn to_display(&self) -> String {
+        match self.try_as_numeric() {
+            Ok(point) => format!("({}, {})", point.x, point.y),
+            Err(_) => "(not a number)".to_owned(),
+        }
+    }
+}
+
+impl CurveFitKind {
+    pub const ALL: [CurveFitKind; 6] = [
+        CurveFitKind::Linear,
+        CurveFitKind::Exponential,
+        CurveFitKind::PowerLaw,
+        CurveFitKind::Logarithmic,
+        CurveFitKind::Polynomial(2),
+        CurveFitKind::Auto,
+    ];
+
+    // candidates for `Auto`, ordered from the simplest to the most involved model
+    const AUTO_CANDIDATES: [CurveFitKind; 4] = [
+        CurveFitKind::Linear,
+        CurveFitKind::Logarithmic,
+        CurveFitKind::Exponential,
+        CurveFitKind::PowerLaw,
+    ];
+
+    #[must_use]
+    #[inline]
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CurveFitKind::Linear => "Linear",
+            CurveFitKind::Exponential => "Exponential",
+            CurveFitKind::PowerLaw => "Power law",
+            CurveFitKind::Logarithmic => "Logarithmic",
+            CurveFitKind::Polynomial(_) => "Polynomial",
+            CurveFitKind::Auto => "Auto",
+        }
+    }
+
+    // maps a point into the space where the model is a straight line, None outside
+    // of the model's domain (e.g. the log of a negative value)
+    fn linearize(&self, p: &PointCoords) -> Option<PointCoords> {
+        let (x, y) = (p.x.into_inner(), p.y.into_inner());
+        let linearized = match self {
+            CurveFitKind::Linear | CurveFitKind::Polynomial(_) | CurveFitKind::Auto => {
+                PointCoords::new(x, y)
+            }
+            CurveFitKind::Exponential => PointCoords::new(x, y.ln()),
+            CurveFitKind::PowerLaw => PointCoords::new(x.ln(), y.ln()),
+            CurveFitKind::Logarithmic => PointCoords::new(x.ln(), y),
+        };
+        linearized.is_finite().then_some(linearized)
+    }
+
+    // polynomials only have their linear terms here, see `evaluate_polynomial`
+    #[must_use]
+    #[inline]
+    pub fn evaluate(&self, slope: f32, intercept: f32, x: f32) -> f32 {
+        match self {
+            CurveFitKind::Linear | CurveFitKind::Polynomial(_) | CurveFitKind::Auto => {
+                slope * x + intercept
+            }
+            CurveFitKind::Exponential => (slope * x + intercept).exp(),
+            CurveFitKind::PowerLaw => (slope * x.ln() + intercept).exp(),
+            CurveFitKind::Logarithmic => slope * x.ln() + intercept,
+        }
+    }
+}
+
+impl EndpointStyle {
+    pub const ALL: [EndpointStyle; 4] = [
+        EndpointStyle::None,
+        EndpointStyle::Circle,
+        EndpointStyle::Arrow,
+        EndpointStyle::Square,
+    ];
+
+    #[must_use]
+    #[inline]
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            EndpointStyle::None => "None",
+            EndpointStyle::Circle => "Circle",
+            EndpointStyle::Arrow => "Arrow",
+            EndpointStyle::Square => "Square",
+        }
+    }
+}
+
+impl Display for CurveFitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurveFitKind::Polynomial(degree) => write!(f, "Polynomial (degree {})", degree),
+            _ => f.write_str(self.display_name()),
+        }
+    }
+}
+
+impl Display for ScreenLineSegment {
+    // one-line summary, e.g. `line_0: y = 1.000x + 2.000 (n=12, R²=0.981)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} (n={}, R²={:.3})",
+            self.name,
+            self.transformed_line_equation(),
+            self.regressor.screen_points.len(),
+            self.r_squared()
+        )
+    }
+}
+
+impl CurveFit {
+    fn fit(kind: CurveFitKind, points: &UniquePointBuf) -> Option<Self> {
+        if kind == CurveFitKind::Auto {
+            return Self::fit_auto(points);
+        }
+        if let CurveFitKind::Polynomial(degree) = kind {
+            return Self::fit_polynomial(degree, points);
+        }
+        let linearized = points
+            .iter()
+            .map(|p| kind.linearize(p))
+            .collect::<Option<UniquePointBuf>>()?;
+        let (slope, intercept) = RegressionLineSegment::get_regression_line(&linearized);
+        Some(
+            CurveFit {
+                kind,
+                slope,
+                intercept,
+                coefficients: Vec::new(),
+                r_squared: 0.0,
+            }
+            .with_r_squared(points),
+        )
+    }
+
+    // None when there are too few points to pin the polynomial down
+    fn fit_polynomial(degree: usize, points: &UniquePointBuf) -> Option<Self> {
+        if points.len() <= degree {
+            return None;
+        }
+        let coefficients = RegressionLineSegment::get_polynomial_regression(points, degree);
+        if !coefficients.iter().all(|c| c.is_finite()) {
+            return None;
+        }
+        Some(
+            CurveFit {
+                kind: CurveFitKind::Polynomial(degree),
+                slope: coefficients.get(1).copied().unwrap_or(0.0),
+                intercept: coefficients[0],
+                coefficients,
+                r_squared: 0.0,
+            }
+            .with_r_squared(points),
+        )
+    }
+
+    #[inline]
+    fn predict(&self, x: f32) -> f32 {
+        match self.kind {
+            CurveFitKind::Polynomial(_) => evaluate_polynomial(&self.coefficients, x),
+            kind => kind.evaluate(self.slope, self.intercept, x),
+        }
+    }
+
+    fn with_r_squared(self, points: &UniquePointBuf) -> Self {
+        // R² is measured on the original data, so the candidates of Auto are comparable
+        CurveFit {
+            r_squared: r_squared_of(points, |x| self.predict(x)),
+            ..self
+        }
+    }
+
+    fn fit_auto(points: &UniquePointBuf) -> Option<Self> {
+        let fits = CurveFitKind::AUTO_CANDIDATES
+            .par_iter()
+            .filter_map(|kind| Self::fit(*kind, points))
+            .collect::<Vec<_>>();
+        let best = fits
+            .iter()
+            .map(|fit| fit.r_squared)
+            .fold(f32::NEG_INFINITY, f32::max);
+        fits.into_iter()
+            .find(|fit| fit.r_squared >= best - AUTO_FIT_R_SQUARED_TOLERANCE)
+    }
+
+    // falls back to a plain linear fit when the data is outside of the model's domain
+    fn fit_or_linear(kind: CurveFitKind, points: &UniquePointBuf) -> Self {
+        Self::fit(kind, points).unwrap_or_else(|| {
+            let (slope, intercept) = RegressionLineSegment::get_regression_line(points);
+            CurveFit {
+                kind: CurveFitKind::Linear,
+                slope,
+                intercept,
+                coefficients: Vec::new(),
+                r_squared: compute_r_squared(points, slope, intercept),
+            }
+        })
+    }
+}
+
+impl LineOrientation {
+    #[must_use]
+    pub fn of(points: &UniquePointBuf) -> Self {
+        let spread = |coord: fn(&PointCoords) -> f32| {
+            let (lo, hi) = points
+                .iter()
+                .map(coord)
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+                    (lo.min(v), hi.max(v))
+                });
+            hi - lo
+        };
+        let spread_x = spread(|p| p.x.into_inner());
+        let spread_y = spread(|p| p.y.into_inner());
+        if spread_x <= ORIENTATION_TOLERANCE * spread_y {
+            LineOrientation::Vertical
+        } else if spread_y <= ORIENTATION_TOLERANCE * spread_x {
+            LineOrientation::Horizontal
+        } else {
+            LineOrientation::General
+        }
+    }
+}
+
+impl MeasurementKind {
+    #[must_use]
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MeasurementKind::Distance => "distance",
+            MeasurementKind::Angle => "angle",
+        }
+    }
+}
+
+impl MeasurementResult {
+    // measures between two screen points in world space, labelled at their midpoint
+    #[must_use]
+    pub fn from_screen_points(
+        kind: MeasurementKind,
+        p1: PointCoords,
+        p2: PointCoords,
+        transform: &PointTransform,
+    ) -> Self {
+        let delta = p2.transform(transform) - p1.transform(transform);
+        let (dx, dy) = (delta.x.into_inner(), delta.y.into_inner());
+        let (world_value, label) = match kind {
+            MeasurementKind::Distance => {
+                let distance = dx.hypot(dy);
+                (distance, format!("{:.3}", distance))
+            }
+            MeasurementKind::Angle => {
+                let angle = dy.atan2(dx).to_degrees();
+                (angle, format!("{:.2}°", angle))
+            }
+        };
+        let midpoint = p1 + p2;
+        MeasurementResult {
+            kind,
+            world_value,
+            label,
+            screen_pos: PointCoords::new(
+                midpoint.x.into_inner() / 2.0,
+                midpoint.y.into_inner() / 2.0,
+            ),
+        }
     }
 }
 
 impl RGBColor {
+    #[must_use]
+    #[inline]
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         RGBColor { r, g, b }
     }
@@ -210,40 +1251,319 @@ impl From<RGBColor> for egui::Color32 {
 }
 
 impl RegressionLineSegment {
+    #[must_use]
     pub fn get_regression_line(points: &UniquePointBuf) -> (f32, f32) {
-        let n = points.len() as f32;
-        let sum_x = points.iter().map(|p| p.x.into_inner()).sum::<f32>();
-        let sum_y = points.iter().map(|p| p.y.into_inner()).sum::<f32>();
-        let sum_x_squared = points
-            .iter()
-            .map(|p| p.x.into_inner() * p.x.into_inner())
-            .sum::<f32>();
-        let sum_xy = points
-            .iter()
-            .map(|p| p.x.into_inner() * p.y.into_inner())
-            .sum::<f32>();
+        // the Gram matrix of [x, y, 1] holds every sum the normal equations need
+        let m = points.to_matrix_faer(true);
+        let gram = m.transpose() * &m;
+        let n = gram[(2, 2)];
+        let sum_x = gram[(0, 2)];
+        let sum_y = gram[(1, 2)];
+        let sum_x_squared = gram[(0, 0)];
+        let sum_xy = gram[(0, 1)];
 
         let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_x_squared - sum_x * sum_x);
         let intercept = (sum_y - slope * sum_x) / n;
         (slope, intercept)
     }
 
+    // total least squares: the line through the centroid along the principal axis of the
+    // centred points, i.e. the right singular vector of the largest singular value
+    #[must_use]
+    pub fn get_orthogonal_regression(points: &UniquePointBuf) -> (f32, f32) {
+        let n = points.len() as f32;
+        let mean_x = points.iter().map(|p| p.x.into_inner()).sum::<f32>() / n;
+        let mean_y = points.iter().map(|p| p.y.into_inner()).sum::<f32>() / n;
+        let mut centred = points.to_matrix_faer(false);
+        for i in 0..points.len() {
+            centred[(i, 0)] -= mean_x;
+            centred[(i, 1)] -= mean_y;
+        }
+        let svd = centred.svd();
+        let axis = svd.v().col(0);
+        let slope = axis[1] / axis[0];
+        (slope, mean_y - slope * mean_x)
+    }
+
+    // least-squares polynomial of the given degree, lowest order coefficient first.
+    // Solves the normal equations VᵀV·c = Vᵀy of the Vandermonde matrix V in f64,
+    // since the powers of x quickly run out of f32 precision.
+    #[must_use]
+    pub fn get_polynomial_regression(points: &UniquePointBuf, degree: usize) -> Vec<f32> {
+        let xy = points.to_matrix_faer(false);
+        let vandermonde = Mat::<f64>::from_fn(xy.nrows(), degree + 1, |i, j| {
+            (xy[(i, 0)] as f64).powi(j as i32)
+        });
+        let y = Mat::<f64>::from_fn(xy.nrows(), 1, |i, _| xy[(i, 1)] as f64);
+        let normal = vandermonde.transpose() * &vandermonde;
+        let rhs = vandermonde.transpose() * &y;
+        let c = normal.full_piv_lu().solve(&rhs);
+        (0..=degree).map(|j| c[(j, 0)] as f32).collect()
+    }
+
+    #[must_use]
     pub fn new(points: UniquePointBuf) -> Self {
-        let (slope, intercept) = RegressionLineSegment::get_regression_line(&points);
+        let fit = CurveFit::fit_or_linear(CurveFitKind::Linear, &points);
         RegressionLineSegment {
-            transformed_slope: slope,
-            transformed_intercept: intercept,
+            transformed_slope: fit.slope,
+            transformed_intercept: fit.intercept,
+            requested_fit: CurveFitKind::Linear,
+            regression_mode: RegressionMode::Ols,
+            fit_space: FitSpace::World,
+            fit_kind: fit.kind,
+            coefficients: fit.coefficients,
+            r_squared: fit.r_squared,
+            orientation: LineOrientation::of(&points),
+            constant_x: Self::mean_x(&points),
             transform: PointTransform::identity(),
             screen_points: points,
+            outliers: UniquePointBuf::new(),
+        }
+    }
+
+    // RANSAC: fits lines through random pairs of points, keeps the one with the most
+    // points within `inlier_threshold` (perpendicular screen distance) and refits on
+    // those, so that a few mis-clicks can't drag the line away. Below two points there
+    // is no pair to sample and every point is kept.
+    #[must_use]
+    pub fn new_ransac(points: UniquePointBuf, iterations: usize, inlier_threshold: f32) -> Self {
+        if points.len() < 2 {
+            return Self::new(points);
+        }
+        let mut rng = StdRng::seed_from_u64(RANSAC_SEED);
+        let mut best_inliers = points.clone();
+        let mut best_count = 0;
+        for _ in 0..iterations {
+            let sample = rand::seq::index::sample(&mut rng, points.len(), 2);
+            let (a, b) = (points[sample.index(0)], points[sample.index(1)]);
+            let delta = b - a;
+            let length = a.distance_to(&b);
+            if length <= f32::EPSILON {
+                continue;
+            }
+            // distance to the line through a and b via the cross product, which also
+            // works for vertical pairs
+            let inliers = points
+                .iter()
+                .filter(|p| {
+                    let offset = **p - a;
+                    let cross = delta.x.into_inner() * offset.y.into_inner()
+                        - delta.y.into_inner() * offset.x.into_inner();
+                    cross.abs() / length <= inlier_threshold
+                })
+                .cloned()
+                .collect::<UniquePointBuf>();
+            if inliers.len() > best_count {
+                best_count = inliers.len();
+                best_inliers = inliers;
+            }
+        }
+        let outliers = points
+            .iter()
+            .filter(|p| !best_inliers.contains(*p))
+            .cloned()
+            .collect();
+        RegressionLineSegment {
+            outliers,
+            ..Self::new(best_inliers)
         }
     }
 
     pub fn transform_line(&mut self, transform: &PointTransform) {
         let transformed_points = self.screen_points.transform(transform);
-        let (slope, intercept) = RegressionLineSegment::get_regression_line(&transformed_points);
+        let fit = self.fit(&transformed_points, transform);
         self.transform = *transform;
-        self.transformed_slope = slope;
-        self.transformed_intercept = intercept;
+        self.transformed_slope = fit.slope;
+        self.transformed_intercept = fit.intercept;
+        self.fit_kind = fit.kind;
+        self.coefficients = fit.coefficients;
+        self.r_squared = fit.r_squared;
+        self.orientation = LineOrientation::of(&transformed_points);
+        self.constant_x = Self::mean_x(&transformed_points);
+    }
+
+    // `points` are the screen points transformed with `transform`
+    fn fit(&self, points: &UniquePointBuf, transform: &PointTransform) -> CurveFit {
+        if self.fit_space == FitSpace::Screen && self.requested_fit == CurveFitKind::Linear {
+            if let Some((slope, intercept)) = self.screen_fit_in_world(transform) {
+                return CurveFit {
+                    kind: CurveFitKind::Linear,
+                    slope,
+                    intercept,
+                    coefficients: Vec::new(),
+                    r_squared: compute_r_squared(points, slope, intercept),
+                };
+            }
+        }
+        if self.regression_mode == RegressionMode::Tls && self.requested_fit == CurveFitKind::Linear
+        {
+            let (slope, intercept) = Self::get_orthogonal_regression(points);
+            return CurveFit {
+                kind: CurveFitKind::Linear,
+                slope,
+                intercept,
+                coefficients: Vec::new(),
+                r_squared: compute_r_squared(points, slope, intercept),
+            };
+        }
+        CurveFit::fit_or_linear(self.requested_fit, points)
+    }
+
+    // the line fitted to the screen points, mapped into world space by two of its points.
+    // None when it ends up vertical in world space and has no slope there
+    fn screen_fit_in_world(&self, transform: &PointTransform) -> Option<(f32, f32)> {
+        let (slope, intercept) = match self.regression_mode {
+            RegressionMode::Ols => Self::get_regression_line(&self.screen_points),
+            RegressionMode::Tls => Self::get_orthogonal_regression(&self.screen_points),
+        };
+        let a = PointCoords::new(0.0, intercept).transform(transform);
+        let b = PointCoords::new(1.0, slope + intercept).transform(transform);
+        let world_slope = (b.y - a.y).into_inner() / (b.x - a.x).into_inner();
+        let world_intercept = a.y.into_inner() - world_slope * a.x.into_inner();
+        (world_slope.is_finite() && world_intercept.is_finite())
+            .then_some((world_slope, world_intercept))
+    }
+
+    fn mean_x(points: &UniquePointBuf) -> f32 {
+        points.iter().map(|p| p.x.into_inner()).sum::<f32>() / points.len() as f32
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn predict_world(&self, x: f32) -> f32 {
+        match self.fit_kind {
+            CurveFitKind::Polynomial(_) => evaluate_polynomial(&self.coefficients, x),
+            kind => kind.evaluate(self.transformed_slope, self.transformed_intercept, x),
+        }
+    }
+
+    // y - ŷ of every screen point in world space, in the iteration order of `screen_points`
+    #[must_use]
+    pub fn residuals_world(&self) -> Vec<f32> {
+        self.screen_points
+            .iter()
+            .map(|p| {
+                let p = p.transform(&self.transform);
+                p.y.into_inner() - self.predict_world(p.x.into_inner())
+            })
+            .collect()
+    }
+
+    // standard deviation of the fitted value at each of `xs`, in world space. Linear fits
+    // use the standard error of the OLS line, other models a residual bootstrap in the
+    // linearised space, since their error isn't symmetric once mapped back.
+    #[must_use]
+    pub fn std_dev_at(&self, xs: &[f32]) -> Vec<f32> {
+        let linearized = self
+            .screen_points
+            .iter()
+            .filter_map(|p| self.fit_kind.linearize(&p.transform(&self.transform)))
+            .collect::<Vec<_>>();
+        let n = linearized.len();
+        if n <= 2 {
+            return vec![0.0; xs.len()];
+        }
+        if self.fit_kind == CurveFitKind::Linear {
+            let mean_x = linearized.iter().map(|p| p.x.into_inner()).sum::<f32>() / n as f32;
+            let s_xx = linearized
+                .iter()
+                .map(|p| (p.x.into_inner() - mean_x).powi(2))
+                .sum::<f32>();
+            let ss_res = self.residuals_world().iter().map(|r| r * r).sum::<f32>();
+            let s = (ss_res / (n - 2) as f32).sqrt();
+            return xs
+                .iter()
+                .map(|x| s * (1.0 / n as f32 + (x - mean_x).powi(2) / s_xx).sqrt())
+                .collect();
+        }
+
+        // the bootstrap resamples in the linearised space, where the model is a line
+        // (or, for polynomials, in world space directly)
+        let fitted = |x: f32| match self.fit_kind {
+            CurveFitKind::Polynomial(_) => evaluate_polynomial(&self.coefficients, x),
+            _ => self.transformed_slope * x + self.transformed_intercept,
+        };
+        let residuals = linearized
+            .iter()
+            .map(|p| p.y.into_inner() - fitted(p.x.into_inner()))
+            .collect::<Vec<_>>();
+        let predictions = (0..BOOTSTRAP_RESAMPLES)
+            .into_par_iter()
+            .map(|i| {
+                let mut rng = StdRng::seed_from_u64(BOOTSTRAP_SEED + i as u64);
+                let resampled = linearized
+                    .iter()
+                    .map(|p| {
+                        let x = p.x.into_inner();
+                        let residual = residuals[rng.gen_range(0..n)];
+                        PointCoords::new(x, fitted(x) + residual)
+                    })
+                    .collect::<UniquePointBuf>();
+                if let CurveFitKind::Polynomial(degree) = self.fit_kind {
+                    let coefficients = Self::get_polynomial_regression(&resampled, degree);
+                    return xs
+                        .iter()
+                        .map(|x| evaluate_polynomial(&coefficients, *x))
+                        .collect::<Vec<_>>();
+                }
+                let (slope, intercept) = Self::get_regression_line(&resampled);
+                xs.iter()
+                    .map(|x| self.fit_kind.evaluate(slope, intercept, *x))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        (0..xs.len())
+            .map(|j| {
+                let values = predictions.iter().map(|p| p[j]).filter(|v| v.is_finite());
+                let (count, sum, sum_sq) = values.fold((0.0, 0.0, 0.0), |(c, s, sq), v| {
+                    (c + 1.0, s + v, sq + v * v)
+                });
+                if count < 2.0 {
+                    return 0.0;
+                }
+                let mean = sum / count;
+                (sum_sq / count - mean * mean).max(0.0).sqrt()
+            })
+            .collect()
+    }
+
+    // |slope without point i - full slope| for every screen point, in world space and
+    // in the iteration order of `screen_points`. Needs one fit per point, each refit goes
+    // through `fit` like `transform_line` so regression mode and fit space are kept.
+    // All zero below three points, where dropping one leaves nothing to fit.
+    #[must_use]
+    pub fn influence_vector(&self) -> Vec<f32> {
+        if self.screen_points.len() < 3 {
+            return vec![0.0; self.screen_points.len()];
+        }
+        self.screen_points
+            .iter()
+            .map(|excluded| {
+                let rest = RegressionLineSegment {
+                    screen_points: self
+                        .screen_points
+                        .iter()
+                        .filter(|p| *p != excluded)
+                        .cloned()
+                        .collect(),
+                    ..self.clone()
+                };
+                let fit = rest.fit(
+                    &rest.screen_points.transform(&self.transform),
+                    &self.transform,
+                );
+                (fit.slope - self.transformed_slope).abs()
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn influence_by_point(&self) -> Vec<(PointCoords, f32)> {
+        self.screen_points
+            .iter()
+            .cloned()
+            .zip(self.influence_vector())
+            .collect()
     }
 
     fn pretty_line_equation<T: Float + Display>(slope: T, intercept: T) -> String {
@@ -253,39 +1573,823 @@ impl RegressionLineSegment {
             format!("y = {:.3}x + {:.3}", slope, intercept)
         }
     }
+
+    fn pretty_curve_equation(&self) -> String {
+        let (slope, intercept) = (self.transformed_slope, self.transformed_intercept);
+        match self.orientation {
+            LineOrientation::Vertical => return format!("x = {:.3}", self.constant_x),
+            LineOrientation::Horizontal if self.fit_kind == CurveFitKind::Linear => {
+                return format!("y = {:.3}", intercept)
+            }
+            _ => {}
+        }
+        match self.fit_kind {
+            CurveFitKind::Linear | CurveFitKind::Auto => {
+                RegressionLineSegment::pretty_line_equation(slope, intercept)
+            }
+            CurveFitKind::Exponential => {
+                format!("y = {:.3}·e^({:.3}x)", intercept.exp(), slope)
+            }
+            CurveFitKind::PowerLaw => format!("y = {:.3}·x^{:.3}", intercept.exp(), slope),
+            CurveFitKind::Logarithmic => {
+                if intercept < 0.0 {
+                    format!("y = {:.3}·ln(x) - {:.3}", slope, -intercept)
+                } else {
+                    format!("y = {:.3}·ln(x) + {:.3}", slope, intercept)
+                }
+            }
+            CurveFitKind::Polynomial(_) => {
+                let mut equation = "y =".to_owned();
+                for (power, c) in self.coefficients.iter().enumerate().rev() {
+                    let term = match power {
+                        0 => String::new(),
+                        1 => "x".to_owned(),
+                        _ => format!("x^{}", power),
+                    };
+                    if power == self.coefficients.len() - 1 {
+                        equation.push_str(&format!(" {:.3}{}", c, term));
+                    } else if *c < 0.0 {
+                        equation.push_str(&format!(" - {:.3}{}", -c, term));
+                    } else {
+                        equation.push_str(&format!(" + {:.3}{}", c, term));
+                    }
+                }
+                equation
+            }
+        }
+    }
+}
+
+// coefficient of determination of the line y = slope·x + intercept over `points`,
+// 1 for a perfect fit. See `r_squared_of` for points that all share the same y.
+#[must_use]
+pub fn compute_r_squared(points: &UniquePointBuf, slope: f32, intercept: f32) -> f32 {
+    r_squared_of(points, |x| slope * x + intercept)
+}
+
+// 1 - SS_res / SS_tot of any model. SS_tot is 0 when all points share the same y, then
+// the fit is either perfect (1) or R² is undefined (NaN).
+fn r_squared_of(points: &UniquePointBuf, predict: impl Fn(f32) -> f32) -> f32 {
+    let n = points.len() as f32;
+    let mean_y = points.iter().map(|p| p.y.into_inner()).sum::<f32>() / n;
+    let (ss_res, ss_tot) = points.iter().fold((0.0, 0.0), |(ss_res, ss_tot), p| {
+        let (x, y) = (p.x.into_inner(), p.y.into_inner());
+        let residual = y - predict(x);
+        (
+            ss_res + residual * residual,
+            ss_tot + (y - mean_y) * (y - mean_y),
+        )
+    });
+    if ss_tot == 0.0 {
+        return if ss_res <= f32::EPSILON {
+            1.0
+        } else {
+            f32::NAN
+        };
+    }
+    1.0 - ss_res / ss_tot
+}
+
+// Horner evaluation of a polynomial given lowest order coefficient first
+#[must_use]
+#[inline]
+pub fn evaluate_polynomial(coefficients: &[f32], x: f32) -> f32 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+// Samples a user formula in `x`, e.g. `2.5*x^2 + 3*x - 1`, at `n_samples` evenly spaced
+// world-space x values; samples where it isn't finite (e.g. 1/x at 0) are skipped
+pub fn sample_formula(
+    formula: &str,
+    (min_x, max_x): (f32, f32),
+    n_samples: usize,
+) -> Result<Vec<PointCoords>, String> {
+    let tree = evalexpr::build_operator_tree(formula).map_err(|e| e.to_string())?;
+    let mut context = HashMapContext::new();
+    let mut points = Vec::with_capacity(n_samples);
+    for i in 0..n_samples {
+        let x = min_x + (max_x - min_x) * i as f32 / (n_samples.max(2) - 1) as f32;
+        context
+            .set_value("x".to_owned(), Value::Float(x as f64))
+            .map_err(|e| e.to_string())?;
+        let y = tree
+            .eval_number_with_context(&context)
+            .map_err(|e| e.to_string())?;
+        let point = PointCoords::new(x, y as f32);
+        if point.is_finite() {
+            points.push(point);
+        }
+    }
+    Ok(points)
+}
+
+// central moments m2, m3 and m4 of `values`
+fn central_moments(values: &[f32]) -> (f32, f32, f32) {
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    values.iter().fold((0.0, 0.0, 0.0), |(m2, m3, m4), v| {
+        let d = v - mean;
+        (m2 + d * d / n, m3 + d.powi(3) / n, m4 + d.powi(4) / n)
+    })
+}
+
+// whether the values only differ by rounding, relative to their size so that tiny residuals
+// (e.g. world units of 1e-4) still have a spread
+fn no_spread(values: &[f32], m2: f32) -> bool {
+    let mean_square = values.iter().map(|v| v * v).sum::<f32>() / values.len() as f32;
+    m2 <= f32::EPSILON * mean_square
+}
+
+// Adjusted Fisher-Pearson sample skewness G1, 0 for symmetric data. NaN for fewer than
+// three values or no spread.
+#[must_use]
+pub fn skewness(values: &[f32]) -> f32 {
+    let n = values.len() as f32;
+    let (m2, m3, _) = central_moments(values);
+    if values.len() < 3 || no_spread(values, m2) {
+        return f32::NAN;
+    }
+    (n * (n - 1.0)).sqrt() / (n - 2.0) * m3 / m2.powf(1.5)
+}
+
+// Sample excess kurtosis G2, 0 for normally distributed data. NaN for fewer than four
+// values or no spread.
+#[must_use]
+pub fn kurtosis(values: &[f32]) -> f32 {
+    let n = values.len() as f32;
+    let (m2, _, m4) = central_moments(values);
+    if values.len() < 4 || no_spread(values, m2) {
+        return f32::NAN;
+    }
+    let g2 = m4 / (m2 * m2) - 3.0;
+    (n - 1.0) / ((n - 2.0) * (n - 3.0)) * ((n + 1.0) * g2 + 6.0)
+}
+
+// Approximate two-sided critical value of Student's t. Only 90%, 95% and 99% are
+// tabulated, other levels use the nearest of them. The normal quantile is corrected for
+// `df` degrees of freedom by the Cornish-Fisher expansion, which is within about 1% from
+// five degrees of freedom on and too narrow below that.
+#[must_use]
+pub fn t_critical(confidence: f32, df: usize) -> f32 {
+    let (_, z) = CONFIDENCE_QUANTILES
+        .iter()
+        .copied()
+        .min_by(|(a, _), (b, _)| (a - confidence).abs().total_cmp(&(b - confidence).abs()))
+        .unwrap();
+    let df = df.max(1) as f32;
+    z + (z.powi(3) + z) / (4.0 * df)
+        + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * df * df)
+}
+
+// y of the polyline through `points` (sorted by x) at `x`, constant beyond its ends
+fn interpolate_y(points: &[PointCoords], x: f32) -> f32 {
+    let idx = points.partition_point(|p| p.x.into_inner() < x);
+    match (
+        idx.checked_sub(1).map(|i| points[i]),
+        points.get(idx).copied(),
+    ) {
+        (_, Some(right)) if right.x.into_inner() == x => right.y.into_inner(),
+        (Some(left), Some(right)) => {
+            let t = (x - left.x.into_inner()) / (right.x - left.x).into_inner();
+            left.y.into_inner() + t * (right.y - left.y).into_inner()
+        }
+        (Some(only), None) | (None, Some(only)) => only.y.into_inner(),
+        (None, None) => f32::NAN,
+    }
+}
+
+// Ray casting: counts the polygon edges crossed by a ray from `point` towards +x. Points on
+// an edge or vertex count as inside. Fewer than three vertices enclose nothing.
+#[must_use]
+pub fn point_in_polygon(point: PointCoords, polygon: &[PointCoords]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+    let (x, y) = (point.x.into_inner(), point.y.into_inner());
+    let mut inside = false;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let (ax, ay) = (a.x.into_inner(), a.y.into_inner());
+        let (bx, by) = (b.x.into_inner(), b.y.into_inner());
+        let cross = (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+        let within_edge_box =
+            x >= ax.min(bx) && x <= ax.max(bx) && y >= ay.min(by) && y <= ay.max(by);
+        if cross.abs() <= f32::EPSILON * (bx - ax).abs().max((by - ay).abs()).max(1.0)
+            && within_edge_box
+        {
+            return true;
+        }
+        if (ay > y) != (by > y) && x < ax + (y - ay) * (bx - ax) / (by - ay) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// Replaces every group of points within `radius` of an unmerged point by the group's
+// centroid, see `remove_duplicates_within`. Greedy and O(n²) in the number of points, meant
+// for the few hundred a user clicks, not for whole images.
+pub fn merge_nearby_points(buf: &mut UniquePointBuf, radius: f32) {
+    *buf = buf.remove_duplicates_within(radius);
+}
+
+// Averages every run of `window` consecutive points of an x-sorted slice, giving
+// n - window + 1 points. Empty when there are fewer points than the window.
+#[must_use]
+pub fn smooth_moving_average(points: &[PointCoords], window: usize) -> Vec<PointCoords> {
+    if window == 0 {
+        return points.to_vec();
+    }
+    points
+        .windows(window)
+        .map(|run| {
+            let (sum_x, sum_y) = run.iter().fold((0.0, 0.0), |(x, y), p| {
+                (x + p.x.into_inner(), y + p.y.into_inner())
+            });
+            PointCoords::new(sum_x / window as f32, sum_y / window as f32)
+        })
+        .collect()
+}
+
+// Removes the point of `buf` closest to `target`, if it is within `tolerance_pixels`.
+// Returns whether a point was removed. The placement order of the rest is kept.
+pub fn remove_nearest(
+    buf: &mut UniquePointBuf,
+    target: PointCoords,
+    tolerance_pixels: f32,
+) -> bool {
+    let nearest = buf
+        .iter()
+        .map(|p| (*p, p.distance_to(&target)))
+        .filter(|(_, dist)| *dist <= tolerance_pixels)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    match nearest {
+        Some((point, _)) => buf.shift_remove(&point),
+        None => false,
+    }
+}
+
+// Cohen-Sutherland region codes
+const OUT_LEFT: u8 = 1;
+const OUT_RIGHT: u8 = 2;
+const OUT_BOTTOM: u8 = 4;
+const OUT_TOP: u8 = 8;
+
+fn region_code(p: PointCoords, (min, max): (PointCoords, PointCoords)) -> u8 {
+    let mut code = 0;
+    if p.x < min.x {
+        code |= OUT_LEFT;
+    } else if p.x > max.x {
+        code |= OUT_RIGHT;
+    }
+    if p.y < min.y {
+        code |= OUT_TOP;
+    } else if p.y > max.y {
+        code |= OUT_BOTTOM;
+    }
+    code
+}
+
+// Cohen-Sutherland clipping of a segment to an axis-aligned (min, max) rectangle,
+// None when the segment lies completely outside of it
+#[must_use]
+pub fn clip_segment(
+    start: PointCoords,
+    end: PointCoords,
+    rect: (PointCoords, PointCoords),
+) -> Option<(PointCoords, PointCoords)> {
+    let (min, max) = rect;
+    let (mut start, mut end) = (start, end);
+    let mut start_code = region_code(start, rect);
+    let mut end_code = region_code(end, rect);
+    loop {
+        if start_code | end_code == 0 {
+            return Some((start, end));
+        }
+        if start_code & end_code != 0 {
+            return None;
+        }
+        // move the outside endpoint onto the edge it crosses
+        let outside = if start_code != 0 {
+            start_code
+        } else {
+            end_code
+        };
+        let (x0, y0) = (start.x.into_inner(), start.y.into_inner());
+        let (x1, y1) = (end.x.into_inner(), end.y.into_inner());
+        let clipped = if outside & OUT_TOP != 0 {
+            let y = min.y.into_inner();
+            PointCoords::new(x0 + (x1 - x0) * (y - y0) / (y1 - y0), y)
+        } else if outside & OUT_BOTTOM != 0 {
+            let y = max.y.into_inner();
+            PointCoords::new(x0 + (x1 - x0) * (y - y0) / (y1 - y0), y)
+        } else if outside & OUT_RIGHT != 0 {
+            let x = max.x.into_inner();
+            PointCoords::new(x, y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+        } else {
+            let x = min.x.into_inner();
+            PointCoords::new(x, y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+        };
+        if outside == start_code {
+            start = clipped;
+            start_code = region_code(start, rect);
+        } else {
+            end = clipped;
+            end_code = region_code(end, rect);
+        }
+    }
+}
+
+// world-space points of every line, each mapped with the transform stored in that line
+#[must_use]
+pub fn all_world_points(lines: &[ScreenLineSegment]) -> Vec<PointCoords> {
+    lines
+        .iter()
+        .flat_map(ScreenLineSegment::raw_point_coords_world)
+        .collect()
+}
+
+// world-space crossing of every pair (i < j) of straight lines, by index into `lines`.
+// Parallel pairs never cross and are left out, as are non-linear fits.
+#[must_use]
+pub fn grid_intersections(lines: &[ScreenLineSegment]) -> Vec<(usize, usize, PointCoords)> {
+    let mut intersections = Vec::new();
+    for (i, a) in lines.iter().enumerate() {
+        for (j, b) in lines.iter().enumerate().skip(i + 1) {
+            if a.fit_kind() != CurveFitKind::Linear || b.fit_kind() != CurveFitKind::Linear {
+                continue;
+            }
+            // -89.99° and 90° are almost parallel, compare the angles modulo 180°
+            let angle_diff = (a.world_slope_degrees() - b.world_slope_degrees()).abs();
+            if angle_diff.min(180.0 - angle_diff) <= PARALLEL_ANGLE_TOLERANCE_DEG {
+                continue;
+            }
+            let (a, b) = (&a.regressor, &b.regressor);
+            let vertical = (
+                a.orientation == LineOrientation::Vertical,
+                b.orientation == LineOrientation::Vertical,
+            );
+            match vertical {
+                (true, true) => continue,
+                (true, false) | (false, true) => {
+                    let (vertical, other) = if vertical.0 { (a, b) } else { (b, a) };
+                    let x = vertical.constant_x;
+                    let p = PointCoords::new(x, other.predict_world(x));
+                    if p.is_finite() {
+                        intersections.push((i, j, p));
+                    }
+                    continue;
+                }
+                (false, false) => {}
+            }
+            let slope_diff = a.transformed_slope - b.transformed_slope;
+            let x = (b.transformed_intercept - a.transformed_intercept) / slope_diff;
+            let p = PointCoords::new(x, a.predict_world(x));
+            if p.is_finite() {
+                intersections.push((i, j, p));
+            }
+        }
+    }
+    intersections
 }
 
 impl ScreenLineSegment {
+    #[must_use]
     pub fn new_from_buf(raw_point_buffer: UniquePointBuf) -> Self {
-        let rightmost = *raw_point_buffer.iter().max_by_key(|p| p.x).unwrap();
-        let leftmost = *raw_point_buffer.iter().min_by_key(|p| p.x).unwrap();
-        let line = RegressionLineSegment::new(raw_point_buffer);
+        Self::from_regressor(RegressionLineSegment::new(raw_point_buffer))
+    }
+
+    // fits only the points RANSAC keeps, the rest are remembered as outliers
+    #[must_use]
+    pub fn new_ransac(
+        raw_point_buffer: UniquePointBuf,
+        iterations: usize,
+        inlier_threshold: f32,
+    ) -> Self {
+        Self::from_regressor(RegressionLineSegment::new_ransac(
+            raw_point_buffer,
+            iterations,
+            inlier_threshold,
+        ))
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn from_regressor(line: RegressionLineSegment) -> Self {
+        let points = &line.screen_points;
+        // a vertical line has no left and right, its ends are the top and bottom instead.
+        // The line is untransformed yet, so its orientation is the one on screen
+        let (rightmost, leftmost) = if line.orientation == LineOrientation::Vertical {
+            (
+                *points.iter().max_by_key(|p| p.y).unwrap(),
+                *points.iter().min_by_key(|p| p.y).unwrap(),
+            )
+        } else {
+            (
+                *points.iter().max_by_key(|p| p.x).unwrap(),
+                *points.iter().min_by_key(|p| p.x).unwrap(),
+            )
+        };
         ScreenLineSegment {
             regressor: line,
+            id: next_line_id(),
+            name: String::new(),
+            visible: true,
             rightmost_pt: rightmost,
             leftmost_pt: leftmost,
             draw_color: RGBColor::random_color(),
+            source: SourceKind::Digitised,
+            show_influence: false,
+            show_band: false,
+            endpoint_style: EndpointStyle::None,
         }
     }
 
+    // builds a line from world-space data, e.g. points loaded from a simulation CSV
+    #[must_use]
+    pub fn from_transform_and_world_points(
+        points: UniquePointBuf,
+        transform: PointTransform,
+    ) -> Self {
+        let screen_points = points.transform(&transform.inverse());
+        let mut line = ScreenLineSegment::new_from_buf(screen_points);
+        line.transform_line(&transform);
+        line
+    }
+
+    // refits the line on the union of its points and `new_points`, keeping its name,
+    // colour and display options
+    #[must_use]
+    pub fn extend_with_points(&self, new_points: UniquePointBuf) -> Self {
+        let mut points = self.regressor.screen_points.clone();
+        points.extend(new_points);
+        let mut extended = ScreenLineSegment::new_from_buf(points);
+        extended.regressor.requested_fit = self.regressor.requested_fit;
+        extended.transform_line(&self.regressor.transform);
+        ScreenLineSegment {
+            regressor: extended.regressor,
+            rightmost_pt: extended.rightmost_pt,
+            leftmost_pt: extended.leftmost_pt,
+            ..self.clone()
+        }
+    }
+
+    // the part of the line inside the rectangle, for painting within an ROI
+    #[must_use]
+    pub fn clip_to_rect(&self, rect: egui::Rect) -> Option<Self> {
+        let (leftmost, rightmost) = clip_segment(
+            self.leftmost_pt,
+            self.rightmost_pt,
+            (rect.min.into(), rect.max.into()),
+        )?;
+        let mut clipped = self.clone();
+        clipped.leftmost_pt = leftmost;
+        clipped.rightmost_pt = rightmost;
+        Some(clipped)
+    }
+
+    // The fitted line extended both ways to where it leaves `roi`, so it spans the whole plot
+    // area even where the data is sparse at the edges. Unchanged when it misses the ROI or is
+    // a single point without a direction.
+    #[must_use]
+    pub fn snap_endpoints_to_roi(&self, roi: egui::Rect) -> Self {
+        let (left, right) = self.fitted_screen_ends();
+        let (left, right) = (egui::Pos2::from(left), egui::Pos2::from(right));
+        if !left.is_finite() || !right.is_finite() || left.distance(right) <= f32::EPSILON {
+            return self.clone();
+        }
+        let direction = (right - left).normalized();
+        // far enough to leave the ROI from anywhere, the clipping cuts it back to the edges
+        let reach = roi.size().length() + roi.center().distance(left);
+        let start = left - direction * reach;
+        let end = right + direction * reach;
+        let Some((leftmost, rightmost)) =
+            clip_segment(start.into(), end.into(), (roi.min.into(), roi.max.into()))
+        else {
+            return self.clone();
+        };
+        let mut snapped = self.clone();
+        snapped.leftmost_pt = leftmost;
+        snapped.rightmost_pt = rightmost;
+        snapped
+    }
+
+    // the points on the fit at the world x of the extreme points (their world y for a
+    // vertical line), back in screen space
+    fn fitted_screen_ends(&self) -> (PointCoords, PointCoords) {
+        let regressor = &self.regressor;
+        let on_fit = |p: PointCoords| {
+            let world = p.transform(&regressor.transform);
+            let on_fit = if regressor.orientation == LineOrientation::Vertical {
+                PointCoords::new(regressor.constant_x, world.y.into_inner())
+            } else {
+                let x = world.x.into_inner();
+                PointCoords::new(x, regressor.predict_world(x))
+            };
+            regressor.transform.apply_inverse(on_fit)
+        };
+        (on_fit(self.leftmost_pt), on_fit(self.rightmost_pt))
+    }
+
+    // screen-space direction from the leftmost to the rightmost point, (1, 0) when both
+    // are the same point
+    #[must_use]
+    pub fn to_unit_vector(&self) -> PointCoords {
+        let length = self.length_in_screen_space();
+        if length <= f32::EPSILON {
+            return PointCoords::new(1.0, 0.0);
+        }
+        let delta = self.rightmost_pt - self.leftmost_pt;
+        PointCoords::new(delta.x.into_inner() / length, delta.y.into_inner() / length)
+    }
+
+    // distance between the extreme points in pixels
+    #[must_use]
+    pub fn length_in_screen_space(&self) -> f32 {
+        self.leftmost_pt.distance_to(&self.rightmost_pt)
+    }
+
+    #[must_use]
+    pub fn length_in_world_space(&self) -> f32 {
+        let transform = &self.regressor.transform;
+        self.leftmost_pt
+            .transform(transform)
+            .distance_to(&self.rightmost_pt.transform(transform))
+    }
+
+    #[must_use]
     pub fn screen_space_slope(&self) -> f32 {
         (self.leftmost_pt - self.rightmost_pt).y.into_inner()
             / (self.leftmost_pt - self.rightmost_pt).x.into_inner()
     }
 
+    #[must_use]
     pub fn screen_space_intercept(&self) -> f32 {
         self.leftmost_pt.y.into_inner()
             - self.screen_space_slope() * self.leftmost_pt.x.into_inner()
     }
 
+    // slope of the fitted model in world space, linearised for non-linear fits
+    #[must_use]
+    #[inline]
+    pub fn world_slope(&self) -> f32 {
+        self.regressor.transformed_slope
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn world_intercept(&self) -> f32 {
+        self.regressor.transformed_intercept
+    }
+
+    // angle of the world-space slope to the x axis in (-90, 90], vertical lines are 90
+    #[must_use]
+    pub fn world_slope_degrees(&self) -> f32 {
+        let slope = self.regressor.transformed_slope;
+        if self.regressor.orientation == LineOrientation::Vertical || slope.is_infinite() {
+            return 90.0;
+        }
+        slope.atan().to_degrees()
+    }
+
     pub fn transform_line(&mut self, transform: &PointTransform) {
         self.regressor.transform_line(transform);
     }
 
+    #[must_use]
+    #[inline]
+    pub fn transform(&self) -> PointTransform {
+        self.regressor.transform
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn regressor(&self) -> &RegressionLineSegment {
+        &self.regressor
+    }
+
+    #[must_use]
+    pub fn screen_points(&self) -> &UniquePointBuf {
+        &self.regressor.screen_points
+    }
+
+    // screen points sorted by x, so that exports are deterministic
+    #[must_use]
+    pub fn raw_point_coords(&self) -> Vec<PointCoords> {
+        let mut points = self
+            .regressor
+            .screen_points
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        points.sort_by_key(|p| (p.x, p.y));
+        points
+    }
+
+    // same order as `raw_point_coords`, so rows of both line up
+    #[must_use]
+    pub fn raw_point_coords_world(&self) -> Vec<PointCoords> {
+        self.raw_point_coords()
+            .iter()
+            .map(|p| p.transform(&self.regressor.transform))
+            .collect()
+    }
+
+    #[must_use]
     pub fn transformed_line_equation(&self) -> String {
-        RegressionLineSegment::pretty_line_equation(
-            self.regressor.transformed_slope,
-            self.regressor.transformed_intercept,
+        self.regressor.pretty_curve_equation()
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn requested_fit(&self) -> CurveFitKind {
+        self.regressor.requested_fit
+    }
+
+    // takes effect on the next `transform_line`
+    #[inline]
+    pub fn set_regression_mode(&mut self, mode: RegressionMode) {
+        self.regressor.regression_mode = mode;
+    }
+
+    // takes effect on the next `transform_line`
+    #[inline]
+    pub fn set_fit_space(&mut self, fit_space: FitSpace) {
+        self.regressor.fit_space = fit_space;
+    }
+
+    // takes effect on the next `transform_line`
+    #[inline]
+    pub fn set_requested_fit(&mut self, kind: CurveFitKind) {
+        self.regressor.requested_fit = kind;
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn fit_kind(&self) -> CurveFitKind {
+        self.regressor.fit_kind
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn r_squared(&self) -> f32 {
+        self.regressor.r_squared
+    }
+
+    // smallest circle around the line's points, in screen space
+    #[must_use]
+    pub fn bounding_circle(&self) -> (PointCoords, f32) {
+        // a line always has at least one point
+        self.regressor.screen_points.min_enclosing_circle().unwrap()
+    }
+
+    // the transform is a similarity, so the circle stays a circle scaled by sqrt(det)
+    #[must_use]
+    pub fn bounding_circle_world(&self) -> (PointCoords, f32) {
+        let (center, radius) = self.bounding_circle();
+        let transform = &self.regressor.transform;
+        (
+            center.transform(transform),
+            radius * transform.determinant().sqrt(),
         )
     }
+
+    #[must_use]
+    #[inline]
+    pub fn outliers(&self) -> &UniquePointBuf {
+        &self.regressor.outliers
+    }
+
+    // orientation of the fitted line in world space
+    #[must_use]
+    #[inline]
+    pub fn orientation(&self) -> LineOrientation {
+        self.regressor.orientation
+    }
+
+    // (x, observed y - fitted y) at each world x, the observed y is interpolated between the
+    // neighbouring points and held at the ends outside of them
+    #[must_use]
+    pub fn sample_residuals_at_x(&self, x_values: &[f32]) -> Vec<(f32, f32)> {
+        let mut world_points = self.raw_point_coords_world();
+        world_points.sort_by_key(|p| p.x);
+        x_values
+            .iter()
+            .map(|&x| {
+                let observed = interpolate_y(&world_points, x);
+                (x, observed - self.regressor.predict_world(x))
+            })
+            .collect()
+    }
+
+    // samples the fitted curve over the world-space extent of the points
+    #[must_use]
+    pub fn world_curve_points(&self, n_samples: usize) -> Vec<PointCoords> {
+        let world_xs = self
+            .regressor
+            .screen_points
+            .iter()
+            .map(|p| p.transform(&self.regressor.transform).x.into_inner());
+        let (min_x, max_x) = world_xs.fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), x| {
+            (lo.min(x), hi.max(x))
+        });
+        (0..n_samples)
+            .map(|i| {
+                let x = min_x + (max_x - min_x) * i as f32 / (n_samples - 1) as f32;
+                PointCoords::new(x, self.regressor.predict_world(x))
+            })
+            .filter(|p| p.is_finite())
+            .collect()
+    }
+
+    // the fitted curve at `n` equally spaced world x between the line's end points, e.g. for
+    // exports that need a regular grid
+    #[must_use]
+    pub fn resample_to_n_points(&self, n: usize) -> Vec<PointCoords> {
+        let transform = &self.regressor.transform;
+        let left = self.leftmost_pt.transform(transform).x.into_inner();
+        let right = self.rightmost_pt.transform(transform).x.into_inner();
+        let step = if n > 1 {
+            (right - left) / (n - 1) as f32
+        } else {
+            0.0
+        };
+        (0..n)
+            .map(|i| {
+                let x = left + step * i as f32;
+                PointCoords::new(x, self.regressor.predict_world(x))
+            })
+            .collect()
+    }
+
+    // world-space length of the fitted curve, the integral of sqrt(1 + (dy/dx)^2) over the
+    // samples with the trapezoidal rule; the derivative is constant for straight lines, so
+    // linear fits get the exact length
+    #[must_use]
+    pub fn arc_length(&self, n_samples: usize) -> f32 {
+        let points = self.world_curve_points(n_samples.max(2));
+        if points.len() < 2 {
+            return 0.0;
+        }
+        let last = points.len() - 1;
+        let integrand = |i: usize| {
+            let (a, b) = (points[i.saturating_sub(1)], points[(i + 1).min(last)]);
+            let dy_dx = ((b.y - a.y) / (b.x - a.x)).into_inner();
+            (1.0 + dy_dx * dy_dx).sqrt()
+        };
+        (0..last)
+            .map(|i| {
+                let dx = (points[i + 1].x - points[i].x).into_inner();
+                0.5 * (integrand(i) + integrand(i + 1)) * dx
+            })
+            .sum()
+    }
+
+    // (lower, upper) screen points of the ±1 standard deviation band around the curve
+    #[must_use]
+    pub fn screen_band_points(&self, n_samples: usize) -> Vec<(PointCoords, PointCoords)> {
+        self.scaled_band_points(n_samples, 1.0)
+    }
+
+    // (lower, upper) screen points of the confidence band of the fitted curve at `n_x`
+    // x values, se·t·sqrt(1/n + (x - x̄)²/Sxx) either side of it. See `t_critical` for the
+    // confidence levels that are supported.
+    #[must_use]
+    pub fn confidence_band(&self, confidence: f32, n_x: usize) -> Vec<(PointCoords, PointCoords)> {
+        let df = self.regressor.screen_points.len().saturating_sub(2);
+        self.scaled_band_points(n_x, t_critical(confidence, df))
+    }
+
+    fn scaled_band_points(&self, n_samples: usize, scale: f32) -> Vec<(PointCoords, PointCoords)> {
+        let curve = self.world_curve_points(n_samples);
+        let xs = curve.iter().map(|p| p.x.into_inner()).collect::<Vec<_>>();
+        let std_devs = self.regressor.std_dev_at(&xs);
+        let transform = &self.regressor.transform;
+        curve
+            .iter()
+            .zip(std_devs)
+            .map(|(p, sd)| {
+                let sd = sd * scale;
+                let (x, y) = (p.x.into_inner(), p.y.into_inner());
+                (
+                    transform.apply_inverse(PointCoords::new(x, y - sd)),
+                    transform.apply_inverse(PointCoords::new(x, y + sd)),
+                )
+            })
+            .collect()
+    }
+
+    // the sampled curve mapped back onto the screen, for models that aren't straight
+    // lines on screen
+    #[must_use]
+    pub fn screen_curve_points(&self, n_samples: usize) -> Vec<PointCoords> {
+        self.world_curve_points(n_samples)
+            .into_iter()
+            .map(|p| self.regressor.transform.apply_inverse(p))
+            .collect()
+    }
 }
@@ -0,0 +1,93 @@
+use std::{fmt::Write as _, io, path::Path};
+
+use sci_shot::io_utils::write_atomic;
+
+use crate::{format_time_of_day, App};
+
+// Markdown summary of the session: where the screenshot came from, every line with its
+// fit statistics, the calibration and the line intersections
+pub fn export_session_report(app: &App, path: &Path) -> io::Result<()> {
+    let mut report = String::new();
+    build_report(app, &mut report).map_err(io::Error::other)?;
+    write_atomic(path, report.as_bytes())
+}
+
+fn build_report(app: &App, out: &mut String) -> std::fmt::Result {
+    writeln!(out, "# Digitisation report")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "- Screenshot: `{}`",
+        app.screenshot_source.description()
+    )?;
+    writeln!(
+        out,
+        "- Captured at: {}",
+        format_time_of_day(app.captured_at)
+    )?;
+    writeln!(out, "- Lines digitised: {}", app.regression_lines.len())?;
+
+    writeln!(out)?;
+    writeln!(out, "## Lines")?;
+    writeln!(out)?;
+    if app.regression_lines.is_empty() {
+        writeln!(out, "No lines were digitised.")?;
+    } else {
+        writeln!(out, "| Name | Equation | Fit | n | R² | MSE |")?;
+        writeln!(out, "|---|---|---|---|---|---|")?;
+        for line in app.regression_lines.iter() {
+            let residuals = line.regressor().residuals_world();
+            let mse = residuals.iter().map(|r| r * r).sum::<f32>() / residuals.len() as f32;
+            writeln!(
+                out,
+                "| {} | `{}` | {} | {} | {:.4} | {:.4e} |",
+                line.name,
+                line.transformed_line_equation(),
+                line.fit_kind(),
+                line.screen_points().len(),
+                line.r_squared(),
+                mse
+            )?;
+        }
+    }
+
+    let transform = &app.target_transform;
+    writeln!(out)?;
+    writeln!(out, "## Calibration")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "World coordinates are `M·(x, -y) + t` of the screen coordinates, with"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "- α (cos θ · scale): {}", transform.alpha)?;
+    writeln!(out, "- β (sin θ · scale): {}", transform.beta)?;
+    writeln!(out, "- t: ({}, {})", transform.dx, transform.dy)?;
+    writeln!(out, "- det: {:.6}", transform.determinant())?;
+
+    let intersections = app.regression_lines.intersection_rows();
+    writeln!(out)?;
+    writeln!(out, "## Intersections")?;
+    writeln!(out)?;
+    if intersections.is_empty() {
+        writeln!(out, "Fewer than two straight lines, nothing intersects.")?;
+    } else {
+        writeln!(out, "| Line A | Line B | World X | World Y |")?;
+        writeln!(out, "|---|---|---|---|")?;
+        for row in intersections {
+            match row.world_pos {
+                Some(p) => writeln!(
+                    out,
+                    "| {} | {} | {:.4} | {:.4} |",
+                    row.line_a, row.line_b, p.x, p.y
+                )?,
+                None => writeln!(
+                    out,
+                    "| {} | {} | parallel | parallel |",
+                    row.line_a, row.line_b
+                )?,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use sci_shot::error::SciShotError;
+use sci_shot::io_utils::write_atomic;
+use sci_shot::line_set::LineSet;
+use sci_shot::point_handling::{PointTransform, ScreenLineSegment, UniquePointBuf};
+use sci_shot::undo::UndoAction;
+
+use crate::{App, ScreenshotSource};
+
+// Everything needed to carry on digitising later: the lines, the points not fitted yet, the
+// calibration and where the screenshot came from.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Session {
+    lines: Vec<ScreenLineSegment>,
+    buffered_points: UniquePointBuf,
+    transform: PointTransform,
+    // None for live captures and pasted images, they can't be opened again
+    screenshot_path: Option<PathBuf>,
+    screenshot_size: [usize; 2],
+    // min and max corner of the crop, in pixels of the uncropped image. Points are stored in
+    // pixels of the cropped one, so the same crop has to be applied when it is reopened.
+    #[serde(default)]
+    crop_rect: Option<[f32; 4]>,
+    // missing in sessions saved before the view was kept
+    #[serde(default)]
+    view: Option<SessionView>,
+}
+
+// zoom and pan of the screenshot, so a locked presentation view comes back as it was
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SessionView {
+    zoom: f32,
+    pan_offset: [f32; 2],
+    locked: bool,
+}
+
+impl App {
+    pub fn save_session(&self, path: &Path) -> Result<(), SciShotError> {
+        let session = Session {
+            lines: self.regression_lines.iter().cloned().collect(),
+            buffered_points: self.buffered_points.clone(),
+            transform: self.target_transform,
+            screenshot_path: match &self.screenshot_source {
+                ScreenshotSource::File(path) => Some(path.clone()),
+                ScreenshotSource::LiveCapture(_) | ScreenshotSource::Clipboard => None,
+            },
+            screenshot_size: self.screenshot_image.as_ref().map_or([0, 0], |i| i.size),
+            crop_rect: self
+                .crop_rect
+                .map(|rect| [rect.min.x, rect.min.y, rect.max.x, rect.max.y]),
+            view: Some(SessionView {
+                zoom: self.zoom,
+                pan_offset: self.pan_offset.into(),
+                locked: self.viewport_locked,
+            }),
+        };
+        let json = serde_json::to_string_pretty(&session)
+            .map_err(|e| SciShotError::ParseError(e.to_string()))?;
+        write_atomic(path, json.as_bytes())?;
+        Ok(())
+    }
+
+    // Replaces the lines, points and calibration with the session's, as one undo step. A
+    // screenshot that came from a file is opened again, otherwise a blank placeholder of the
+    // same size stands in for it.
+    pub fn load_session(&mut self, ctx: &egui::Context, path: &Path) -> Result<(), SciShotError> {
+        let json = std::fs::read_to_string(path)?;
+        let session: Session =
+            serde_json::from_str(&json).map_err(|e| SciShotError::ParseError(e.to_string()))?;
+
+        let mut lines = LineSet::default();
+        for line in session.lines {
+            lines.add(line);
+        }
+        self.regression_lines = lines;
+        self.buffered_points = session.buffered_points;
+        self.selected_line_idx = None;
+        self.checked_lines.clear();
+        self.record(UndoAction::LoadSession);
+        self.apply_calibration(session.transform);
+
+        let crop = session.crop_rect.map(|[min_x, min_y, max_x, max_y]| {
+            egui::Rect::from_min_max(egui::pos2(min_x, min_y), egui::pos2(max_x, max_y))
+        });
+        let reopened = match session.screenshot_path {
+            Some(screenshot) => {
+                self.set_screenshot_source(ctx, ScreenshotSource::File(screenshot), crop)
+            }
+            None => false,
+        };
+        if !reopened && session.screenshot_size != [0, 0] {
+            self.live_capture = false;
+            let placeholder =
+                egui::ColorImage::new(session.screenshot_size, egui::Color32::DARK_GRAY);
+            self.upload_screenshot(ctx, placeholder);
+            // kept so that saving again doesn't lose it
+            self.crop_rect = crop;
+        }
+        if let Some(view) = session.view {
+            self.zoom = view.zoom;
+            self.pan_offset = view.pan_offset.into();
+            self.viewport_locked = view.locked;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use crate::point_handling::PointCoords;
+use bounded_vec_deque::BoundedVecDeque;
+use std::fmt::Display;
+
+// what a step of the point and line history did, named in the status bar when it is undone
+#[derive(Clone)]
+pub enum UndoAction {
+    AddPoint(PointCoords),
+    RemovePoint(PointCoords),
+    // name of the added line
+    AddLine(String),
+    // index the line had before it was removed
+    RemoveLine(usize),
+    // several lines removed at once, by count
+    RemoveLines(usize),
+    // buffered points moved into the line at this index
+    ExtendLine(usize),
+    // the line at this index was changed in place, e.g. its endpoints moved
+    EditLine(usize),
+    // the buffered points were rewritten as a whole, e.g. merged or smoothed
+    ReplacePoints,
+    // the lines and points were replaced by a saved session
+    LoadSession,
+}
+
+impl UndoAction {
+    // whether undoing this can change the lines, which makes their indices stale
+    #[must_use]
+    pub fn changes_lines(&self) -> bool {
+        !matches!(
+            self,
+            UndoAction::AddPoint(_) | UndoAction::RemovePoint(_) | UndoAction::ReplacePoints
+        )
+    }
+}
+
+impl Display for UndoAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UndoAction::AddPoint(p) => write!(f, "add point ({:.1}, {:.1})", p.x, p.y),
+            UndoAction::RemovePoint(p) => write!(f, "remove point ({:.1}, {:.1})", p.x, p.y),
+            UndoAction::AddLine(name) => write!(f, "add {}", name),
+            UndoAction::RemoveLine(idx) => write!(f, "remove line #{}", idx + 1),
+            UndoAction::RemoveLines(count) => write!(f, "remove {} lines", count),
+            UndoAction::ExtendLine(idx) => write!(f, "extend line #{}", idx + 1),
+            UndoAction::EditLine(idx) => write!(f, "edit line #{}", idx + 1),
+            UndoAction::ReplacePoints => f.write_str("edit points"),
+            UndoAction::LoadSession => f.write_str("load session"),
+        }
+    }
+}
+
+// Snapshot based undo history. The most recent entry of `past` is the current state, so
+// `undo` and `redo` hand back the state to restore rather than the one being left.
+pub struct UndoStack<T> {
+    past: BoundedVecDeque<T>,
+    future: BoundedVecDeque<T>,
+}
+
+impl<T: Clone> UndoStack<T> {
+    // `initial` is the state undo bottoms out at, it counts towards the capacity
+    pub fn new(initial: T, capacity: usize) -> Self {
+        // one slot is always taken by the current state
+        let capacity = capacity.max(1);
+        let mut past = BoundedVecDeque::new(capacity);
+        past.push_back(initial);
+        UndoStack {
+            past,
+            future: BoundedVecDeque::new(capacity),
+        }
+    }
+
+    // records a new current state, dropping anything that could have been redone
+    pub fn push(&mut self, item: T) {
+        let _ = self.past.push_back(item);
+        self.future.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<T> {
+        if self.past.len() < 2 {
+            return None;
+        }
+        let left = self.past.pop_back()?;
+        let _ = self.future.push_back(left);
+        self.past.back().cloned()
+    }
+
+    pub fn redo(&mut self) -> Option<T> {
+        let item = self.future.pop_back()?;
+        let _ = self.past.push_back(item.clone());
+        Some(item)
+    }
+
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        self.past.len() > 1
+    }
+
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+
+    // rewrites every state, e.g. when what they refer to moved
+    pub fn for_each_mut(&mut self, mut f: impl FnMut(&mut T)) {
+        self.past.iter_mut().for_each(&mut f);
+        self.future.iter_mut().for_each(&mut f);
+    }
+
+    // states that can be returned to, oldest first, ending with the current one
+    #[must_use]
+    pub fn past(&self) -> &BoundedVecDeque<T> {
+        &self.past
+    }
+}
@@ -0,0 +1,43 @@
+use eframe::egui;
+use sci_shot::point_handling::{PointCoords, PointTransform, RGBColor};
+
+// text pinned to a point in world coordinates, it follows the calibration when it changes
+#[derive(Debug, Clone)]
+pub struct WorldSpaceLabel {
+    pub world_pos: PointCoords,
+    pub text: String,
+    pub color: RGBColor,
+}
+
+impl WorldSpaceLabel {
+    #[must_use]
+    pub fn new(world_pos: PointCoords, text: impl Into<String>, color: RGBColor) -> Self {
+        WorldSpaceLabel {
+            world_pos,
+            text: text.into(),
+            color,
+        }
+    }
+}
+
+// maps every label back to screenshot pixels with `transform` and paints it just above and to
+// the right of that pixel
+pub fn draw_world_labels(
+    labels: &[WorldSpaceLabel],
+    painter: &egui::Painter,
+    transform: &PointTransform,
+) {
+    for label in labels {
+        let screen_pos: egui::Pos2 = transform.apply_inverse(label.world_pos).into();
+        if !screen_pos.is_finite() {
+            continue;
+        }
+        painter.text(
+            screen_pos,
+            egui::Align2::LEFT_BOTTOM,
+            &label.text,
+            egui::FontId::proportional(14.0),
+            label.color.into(),
+        );
+    }
+}
@@ -0,0 +1,573 @@
+use proptest::prelude::*;
+use sci_shot::{
+    axis_detection::{detect_axis_scale, Axis},
+    export::spawn_io_thread,
+    io_utils::{tmp_path, write_atomic},
+    point_handling::{
+        compute_r_squared, grid_intersections, kurtosis, merge_nearby_points, point_in_polygon,
+        skewness, AxisTick, CurveFitKind, MeasurementKind, RegressionMode, ToCsvRow,
+    },
+    CsvFormat, ExportSettings, FitSpace, IoRequest, IoResponse, MeasurementResult, PointCoords,
+    PointTransform, RGBColor, RegressionLineSegment, SciShotError, ScreenLineSegment,
+    Transformable, UniquePointBuf,
+};
+
+#[test]
+fn calibration_maps_the_reference_points() {
+    let pairs = [
+        (PointCoords::new(100.0, 500.0), PointCoords::new(0.0, 0.0)),
+        (PointCoords::new(300.0, 500.0), PointCoords::new(10.0, 0.0)),
+    ];
+    let transform = PointTransform::interpolate_from_point_pairs(pairs[0], pairs[1]).unwrap();
+    for (screen, world) in pairs {
+        assert!(screen.transform(&transform).approx_eq(&world, 1e-3));
+    }
+}
+
+#[test]
+fn calibration_rejects_coincident_points() {
+    let p = PointCoords::new(1.0, 1.0);
+    let result = PointTransform::interpolate_from_point_pairs(
+        (p, PointCoords::new(0.0, 0.0)),
+        (p, PointCoords::new(1.0, 1.0)),
+    );
+    assert!(matches!(result, Err(SciShotError::CalibrationError(_))));
+}
+
+#[test]
+fn r_squared_matches_the_analytic_value() {
+    // ȳ = 4/3, SS_tot = 24/9 and SS_res = 1 for y = x, so R² = 1 - 9/24
+    let points = [(0.0, 0.0), (1.0, 2.0), (2.0, 2.0)]
+        .into_iter()
+        .map(|(x, y)| PointCoords::new(x, y))
+        .collect::<UniquePointBuf>();
+    assert!((compute_r_squared(&points, 1.0, 0.0) - 0.625).abs() < 1e-6);
+}
+
+#[test]
+fn r_squared_of_constant_data() {
+    let points = (0..5)
+        .map(|i| PointCoords::new(i as f32, 3.0))
+        .collect::<UniquePointBuf>();
+    assert_eq!(compute_r_squared(&points, 0.0, 3.0), 1.0);
+    assert!(compute_r_squared(&points, 1.0, 0.0).is_nan());
+}
+
+#[test]
+fn regression_recovers_an_exact_line() {
+    let points = (0..10)
+        .map(|i| PointCoords::new(i as f32, 2.0 * i as f32 + 1.0))
+        .collect::<UniquePointBuf>();
+    let (slope, intercept) = RegressionLineSegment::get_regression_line(&points);
+    assert!((slope - 2.0).abs() < 1e-4);
+    assert!((intercept - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn csv_export_round_trips() {
+    let dir = std::env::temp_dir().join(format!("sci_shot_test_{}", std::process::id()));
+    let file_path = dir.join("line_0.csv");
+    let world_points = vec![PointCoords::new(0.5, 1.5), PointCoords::new(2.0, -3.25)];
+    let (sender, receiver) = spawn_io_thread();
+    sender
+        .send(IoRequest::SaveLine {
+            line_id: 0,
+            points: world_points.clone(),
+            world_points: world_points.clone(),
+            transform: PointTransform::identity(),
+            csv_format: CsvFormat::WorldOnly,
+            file_path: file_path.clone(),
+            settings: ExportSettings::default(),
+        })
+        .unwrap();
+    assert!(matches!(receiver.recv().unwrap(), IoResponse::Saved { .. }));
+
+    sender
+        .send(IoRequest::LoadCsv {
+            file_path,
+            color: RGBColor::new(0, 0, 0),
+        })
+        .unwrap();
+    match receiver.recv().unwrap() {
+        IoResponse::Loaded { points, .. } => assert_eq!(points, world_points),
+        _ => panic!("loading the exported file failed"),
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn csv_export_with_both_coordinates_loads_world_columns() {
+    let dir = std::env::temp_dir().join(format!("sci_shot_both_{}", std::process::id()));
+    let file_path = dir.join("line_0.csv");
+    let points = vec![PointCoords::new(10.0, 20.0), PointCoords::new(30.0, 40.0)];
+    let transform = PointTransform::new(2.0, 0.0, 1.0, -1.0);
+    let world_points = points
+        .iter()
+        .map(|p| p.transform(&transform))
+        .collect::<Vec<_>>();
+    let (sender, receiver) = spawn_io_thread();
+    sender
+        .send(IoRequest::SaveLine {
+            line_id: 0,
+            points: points.clone(),
+            world_points: world_points.clone(),
+            transform,
+            csv_format: CsvFormat::Both,
+            file_path: file_path.clone(),
+            settings: ExportSettings::default(),
+        })
+        .unwrap();
+    assert!(matches!(receiver.recv().unwrap(), IoResponse::Saved { .. }));
+    let contents = std::fs::read_to_string(&file_path).unwrap();
+    assert_eq!(
+        contents.lines().next(),
+        Some("screen_x,screen_y,world_x,world_y")
+    );
+
+    sender
+        .send(IoRequest::LoadCsv {
+            file_path,
+            color: RGBColor::new(0, 0, 0),
+        })
+        .unwrap();
+    match receiver.recv().unwrap() {
+        IoResponse::Loaded { points, .. } => assert_eq!(points, world_points),
+        _ => panic!("loading the exported file failed"),
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn residuals_are_sampled_between_points() {
+    // screen y points down, so the world points are (0, 0), (1, 1), (2, 0)
+    let points = [(0.0, 0.0), (1.0, -1.0), (2.0, 0.0)]
+        .iter()
+        .map(|&(x, y)| PointCoords::new(x, y))
+        .collect::<UniquePointBuf>();
+    let mut line = ScreenLineSegment::new_from_buf(points);
+    line.set_requested_fit(CurveFitKind::Linear);
+    line.transform_line(&PointTransform::identity());
+    let residuals = line.sample_residuals_at_x(&[0.0, 0.5, 1.0]);
+    // the fit is y = 1/3, the data at x = 0.5 is interpolated to 0.5
+    let expected = [-1.0 / 3.0, 0.5 - 1.0 / 3.0, 2.0 / 3.0];
+    for ((x, residual), (expected_x, expected_residual)) in
+        residuals.iter().zip([0.0, 0.5, 1.0].iter().zip(expected))
+    {
+        assert_eq!(x, expected_x);
+        assert!((residual - expected_residual).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn atomic_writes_leave_the_old_file_or_the_new_one() {
+    let dir = std::env::temp_dir().join(format!("sci_shot_atomic_{}", std::process::id()));
+    let path = dir.join("points.csv");
+    write_atomic(&path, b"old,contents\n").unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"old,contents\n");
+
+    // a directory in the way of the temporary file makes the write fail halfway
+    std::fs::create_dir_all(tmp_path(&path)).unwrap();
+    assert!(write_atomic(&path, b"new,contents\n").is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"old,contents\n");
+    let missing = dir.join("missing.csv");
+    std::fs::create_dir_all(tmp_path(&missing)).unwrap();
+    assert!(write_atomic(&missing, b"new,contents\n").is_err());
+    assert!(!missing.exists());
+
+    std::fs::remove_dir(tmp_path(&path)).unwrap();
+    write_atomic(&path, b"new,contents\n").unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"new,contents\n");
+    assert!(!tmp_path(&path).exists());
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn resampling_spaces_x_evenly_along_the_fit() {
+    // screen y points down, so the world line is y = 2x + 1
+    let points = (0..5)
+        .map(|i| PointCoords::new(i as f32, -(2.0 * i as f32 + 1.0)))
+        .collect::<UniquePointBuf>();
+    let mut line = ScreenLineSegment::new_from_buf(points);
+    line.set_requested_fit(CurveFitKind::Linear);
+    line.transform_line(&PointTransform::identity());
+    let resampled = line.resample_to_n_points(3);
+    assert_eq!(resampled.len(), 3);
+    for (p, (x, y)) in resampled.iter().zip([(0.0, 1.0), (2.0, 5.0), (4.0, 9.0)]) {
+        assert!(p.approx_eq(&PointCoords::new(x, y), 1e-4));
+    }
+}
+
+#[test]
+fn inverted_transform_maps_points_back() {
+    let transforms = [
+        PointTransform::identity(),
+        PointTransform::new(0.5, -0.25, 12.0, -3.5),
+        PointTransform::new(-2.0, 3.0, -100.0, 40.0),
+        PointTransform::new(0.01, 0.0, 0.5, 0.25),
+    ];
+    let points = [
+        PointCoords::new(0.0, 0.0),
+        PointCoords::new(1.5, -2.25),
+        PointCoords::new(640.0, 480.0),
+    ];
+    for transform in &transforms {
+        let inverse = transform.invert().unwrap();
+        for p in &points {
+            let round_trip = p.transform(transform).transform(&inverse);
+            assert!(
+                round_trip.approx_eq(p, 1e-3),
+                "{:?} came back as {:?}",
+                p,
+                round_trip
+            );
+        }
+    }
+    assert!(PointTransform::new(0.0, 0.0, 1.0, 1.0).invert().is_none());
+}
+
+#[test]
+fn residual_moments_use_the_unbiased_estimators() {
+    let uniform = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert!(skewness(&uniform).abs() < 1e-5);
+    assert!((kurtosis(&uniform) + 1.2).abs() < 1e-4);
+    assert!(skewness(&[0.0, 0.0, 0.0, 1.0, 10.0]) > 0.0);
+    assert!(kurtosis(&[1.0, 2.0, 3.0]).is_nan());
+}
+
+proptest! {
+    #[test]
+    fn transforms_near_equal_themselves(
+        alpha in -1e3f32..1e3,
+        beta in -1e3f32..1e3,
+        dx in -1e6f32..1e6,
+        dy in -1e6f32..1e6,
+    ) {
+        let t = PointTransform::new(alpha, beta, dx, dy);
+        prop_assert!(t.near_equals(&t, 1e-10));
+        prop_assert_eq!(t, t);
+    }
+}
+
+proptest! {
+    #[test]
+    fn composed_transforms_apply_one_after_the_other(
+        a in (-10f32..10.0, -10f32..10.0, -100f32..100.0, -100f32..100.0),
+        b in (-10f32..10.0, -10f32..10.0, -100f32..100.0, -100f32..100.0),
+        x in -1e3f32..1e3,
+        y in -1e3f32..1e3,
+    ) {
+        let a = PointTransform::new(a.0, a.1, a.2, a.3);
+        let b = PointTransform::new(b.0, b.1, b.2, b.3);
+        let p = PointCoords::new(x, y);
+        let world = p.transform(&a);
+        // `b` works on world coordinates, undo the y flip `transform` applies to screen points
+        let expected = PointCoords::new(world.x.into_inner(), -world.y.into_inner()).transform(&b);
+        let composed = p.transform(&(a * b));
+        let scale = expected.x.into_inner().abs().max(expected.y.into_inner().abs());
+        let tolerance = 1e-3 * (1.0 + scale);
+        prop_assert!(composed.approx_eq(&expected, tolerance), "{:?} != {:?}", composed, expected);
+    }
+}
+
+#[test]
+fn screen_space_fits_map_the_screen_line_into_world_space() {
+    let points = [(0.0, 1.0), (1.0, 2.5), (2.0, 5.5), (3.0, 6.0), (4.0, 9.5)]
+        .iter()
+        .map(|&(x, y)| PointCoords::new(x, y))
+        .collect::<UniquePointBuf>();
+    let (slope, intercept) = RegressionLineSegment::get_regression_line(&points);
+    let angle = 0.4_f32;
+    let transform = PointTransform::new(2.0 * angle.cos(), 2.0 * angle.sin(), 3.0, -1.0);
+    let mut line = ScreenLineSegment::new_from_buf(points);
+    line.set_requested_fit(CurveFitKind::Linear);
+    line.set_fit_space(FitSpace::Screen);
+    line.transform_line(&transform);
+    for x in [-1.0, 0.0, 5.0] {
+        let on_screen_line = PointCoords::new(x, slope * x + intercept).transform(&transform);
+        let predicted = line.world_slope() * on_screen_line.x.into_inner() + line.world_intercept();
+        assert!((predicted - on_screen_line.y.into_inner()).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn polygon_contains_points_inside_and_on_its_edges() {
+    let triangle = [
+        PointCoords::new(0.0, 0.0),
+        PointCoords::new(4.0, 0.0),
+        PointCoords::new(0.0, 4.0),
+    ];
+    assert!(point_in_polygon(PointCoords::new(1.0, 1.0), &triangle));
+    assert!(!point_in_polygon(PointCoords::new(3.0, 3.0), &triangle));
+    assert!(!point_in_polygon(PointCoords::new(-1.0, 1.0), &triangle));
+    assert!(point_in_polygon(PointCoords::new(2.0, 2.0), &triangle));
+    assert!(point_in_polygon(PointCoords::new(2.0, 0.0), &triangle));
+    assert!(!point_in_polygon(
+        PointCoords::new(1.0, 1.0),
+        &triangle[..2]
+    ));
+}
+
+#[test]
+fn least_squares_calibration_averages_out_noisy_pairs() {
+    let truth = PointTransform::new(0.5, 0.2, 10.0, -4.0);
+    let noise = [0.01, -0.02, 0.015, -0.005, 0.0];
+    let pairs = [
+        (0.0, 0.0),
+        (100.0, 0.0),
+        (0.0, 80.0),
+        (60.0, 40.0),
+        (30.0, 90.0),
+    ]
+    .iter()
+    .zip(noise)
+    .map(|(&(x, y), e)| {
+        let screen = PointCoords::new(x, y);
+        let world = screen.transform(&truth);
+        (
+            screen,
+            PointCoords::new(world.x.into_inner() + e, world.y.into_inner() - e),
+        )
+    })
+    .collect::<Vec<_>>();
+    let fitted = PointTransform::fit_from_point_pairs(&pairs).unwrap();
+    assert!(fitted.near_equals(&truth, 0.02), "{:?}", fitted);
+
+    // two noise-free pairs pin the transform down, the fit has nothing to average
+    let clean = [PointCoords::new(0.0, 0.0), PointCoords::new(100.0, 0.0)]
+        .map(|screen| (screen, screen.transform(&truth)));
+    let exact = PointTransform::fit_from_point_pairs(&clean).unwrap();
+    assert!(exact.near_equals(&truth, 1e-4), "{:?}", exact);
+    assert!(PointTransform::fit_from_point_pairs(&pairs[..1]).is_err());
+}
+
+#[test]
+fn slope_angles_are_in_degrees_and_parallel_lines_do_not_intersect() {
+    // `transform` flips y, so the screen points go down to the right for rising world lines
+    let line = |slope: f32, intercept: f32| {
+        let points = (0..5)
+            .map(|x| PointCoords::new(x as f32, -(slope * x as f32 + intercept)))
+            .collect::<UniquePointBuf>();
+        let mut line = ScreenLineSegment::new_from_buf(points);
+        line.set_requested_fit(CurveFitKind::Linear);
+        line.transform_line(&PointTransform::identity());
+        line
+    };
+    let rising = line(1.0, 0.0);
+    assert!((rising.world_slope_degrees() - 45.0).abs() < 1e-3);
+    assert!((line(-3f32.sqrt(), 2.0).world_slope_degrees() + 60.0).abs() < 1e-3);
+
+    let lines = [rising, line(1.0, 3.0), line(0.0, 1.0)];
+    let pairs = grid_intersections(&lines)
+        .into_iter()
+        .map(|(i, j, _)| (i, j))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![(0, 2), (1, 2)]);
+}
+
+#[test]
+fn nearby_points_merge_into_their_centroids() {
+    let mut buf = [
+        (0.0, 0.0),
+        (2.0, 0.0),
+        (1.0, 3.0),
+        (20.0, 20.0),
+        (21.0, 20.0),
+    ]
+    .iter()
+    .map(|&(x, y)| PointCoords::new(x, y))
+    .collect::<UniquePointBuf>();
+    merge_nearby_points(&mut buf, 4.0);
+    assert_eq!(buf.len(), 2);
+    assert!(buf[0].approx_eq(&PointCoords::new(1.0, 1.0), 1e-5));
+    assert!(buf[1].approx_eq(&PointCoords::new(20.5, 20.0), 1e-5));
+}
+
+#[test]
+fn axis_ticks_with_different_scales_are_rejected() {
+    let tick = |x: f32, y: f32, value: f32| AxisTick {
+        screen_pos: PointCoords::new(x, y),
+        value,
+    };
+    let horizontal = [tick(100.0, 400.0, 0.0), tick(300.0, 400.0, 10.0)];
+    // 10 units per 200 px on both axes, the y axis grows upwards
+    let square = [tick(50.0, 350.0, 0.0), tick(50.0, 150.0, 10.0)];
+    let transform = PointTransform::from_axis_ticks(horizontal, square).unwrap();
+    let world = PointCoords::new(300.0, 150.0).transform(&transform);
+    assert!(
+        world.approx_eq(&PointCoords::new(10.0, 10.0), 1e-3),
+        "{:?}",
+        world
+    );
+
+    let stretched = [tick(50.0, 350.0, 0.0), tick(50.0, 150.0, 100.0)];
+    assert!(PointTransform::from_axis_ticks(horizontal, stretched).is_err());
+    let downwards = [tick(50.0, 350.0, 10.0), tick(50.0, 150.0, 0.0)];
+    assert!(PointTransform::from_axis_ticks(horizontal, downwards).is_err());
+}
+
+#[test]
+fn snapped_endpoints_lie_on_the_fitted_line() {
+    // the extreme points sit off the fit, their chord is steeper than the regression line
+    let points = [
+        (10.0, -12.0),
+        (20.0, -19.0),
+        (30.0, -31.0),
+        (40.0, -41.0),
+        (50.0, -48.0),
+    ]
+    .iter()
+    .map(|&(x, y)| PointCoords::new(x, y))
+    .collect::<UniquePointBuf>();
+    let mut line = ScreenLineSegment::new_from_buf(points);
+    line.set_requested_fit(CurveFitKind::Linear);
+    line.transform_line(&PointTransform::identity());
+    let roi = egui::Rect::from_min_max(egui::pos2(0.0, -100.0), egui::pos2(60.0, 0.0));
+    let snapped = line.snap_endpoints_to_roi(roi);
+    for end in [snapped.leftmost_pt, snapped.rightmost_pt] {
+        let world = end.transform(&PointTransform::identity());
+        let fitted = line.world_slope() * world.x.into_inner() + line.world_intercept();
+        assert!((world.y.into_inner() - fitted).abs() < 1e-3, "{:?}", end);
+        let on_edge = [end.x.into_inner(), -end.y.into_inner()]
+            .iter()
+            .any(|v| v.abs() < 1e-3 || (v - 60.0).abs() < 1e-3 || (v - 100.0).abs() < 1e-3);
+        assert!(on_edge, "{:?}", end);
+    }
+}
+
+#[test]
+fn residual_moments_do_not_depend_on_the_units() {
+    let values = [1.0_f32, 2.0, 2.5, 4.0, 7.5, 3.0];
+    let tiny = values.map(|v| v * 1e-4);
+    assert!((skewness(&tiny) - skewness(&values)).abs() < 1e-3);
+    assert!((kurtosis(&tiny) - kurtosis(&values)).abs() < 1e-3);
+    assert!(skewness(&[0.1; 5]).is_nan());
+    assert!(kurtosis(&[0.0; 5]).is_nan());
+}
+
+#[test]
+fn ransac_keeps_the_same_inliers_every_time() {
+    // two near-collinear groups, so an unlucky sample could settle on either
+    let points = (0..12)
+        .map(|i| {
+            let x = i as f32 * 10.0;
+            let y = if i % 3 == 0 { 3.0 * x + 40.0 } else { x };
+            PointCoords::new(x, y)
+        })
+        .collect::<UniquePointBuf>();
+    let first = ScreenLineSegment::new_ransac(points.clone(), 20, 1.0);
+    for _ in 0..5 {
+        let again = ScreenLineSegment::new_ransac(points.clone(), 20, 1.0);
+        assert_eq!(again.outliers(), first.outliers());
+    }
+}
+
+#[test]
+fn ransac_keeps_every_point_when_there_is_no_pair() {
+    // a screen line needs a point for its ends, so the empty case stays in the regressor
+    let empty = RegressionLineSegment::new_ransac(UniquePointBuf::new(), 20, 1.0);
+    assert!(empty.influence_by_point().is_empty());
+
+    let points = [PointCoords::new(1.0, 1.0)]
+        .into_iter()
+        .collect::<UniquePointBuf>();
+    let line = ScreenLineSegment::new_ransac(points.clone(), 20, 1.0);
+    assert!(line.outliers().is_empty());
+    assert_eq!(line.screen_points(), &points);
+}
+
+#[test]
+fn ransac_leaves_a_planted_outlier_out_of_the_fit() {
+    let outlier = PointCoords::new(50.0, 400.0);
+    let points = (0..10)
+        .map(|i| PointCoords::new(i as f32 * 10.0, i as f32 * 5.0))
+        .chain([outlier])
+        .collect::<UniquePointBuf>();
+    let mut line = ScreenLineSegment::new_ransac(points, 50, 1.0);
+    line.transform_line(&PointTransform::identity());
+    assert!(line.outliers().contains(&outlier));
+    assert_eq!(line.outliers().len(), 1);
+    assert!(!line.screen_points().contains(&outlier));
+    // y = 0.5x on screen, i.e. -0.5 in world space under the identity
+    assert!(
+        (line.world_slope() + 0.5).abs() < 1e-4,
+        "{}",
+        line.world_slope()
+    );
+}
+
+#[test]
+fn projection_moves_points_along_the_normal_onto_the_line() {
+    // y = 2x + 1, with (-2, 1) normal to it
+    let on_line = PointCoords::new(1.0, 3.0);
+    assert!(on_line
+        .project_onto_line(2.0, 1.0)
+        .approx_eq(&on_line, 1e-5));
+    let off_line = PointCoords::new(1.0 - 2.0 * 1.5, 3.0 + 1.5);
+    assert!(off_line
+        .project_onto_line(2.0, 1.0)
+        .approx_eq(&on_line, 1e-5));
+}
+
+#[test]
+fn csv_rows_join_their_fields_with_commas() {
+    let screen = PointCoords::new(1.5, -2.0);
+    assert_eq!(screen.to_csv_row(), "1.5,-2");
+    assert_eq!(
+        (screen, PointCoords::new(0.25, 4.0)).to_csv_row(),
+        "1.5,-2,0.25,4"
+    );
+    let measurement = MeasurementResult {
+        kind: MeasurementKind::Angle,
+        world_value: 45.0,
+        label: "45°".to_owned(),
+        screen_pos: screen,
+    };
+    assert_eq!(measurement.to_csv_row(), "angle,45,1.5,-2");
+}
+
+#[test]
+fn axis_scale_is_the_tick_spacing() {
+    // white strip with one pixel wide black ticks at 7, 27, 47, ...
+    let ticks = |width: u32, height: u32, axis: Axis| {
+        image::RgbaImage::from_fn(width, height, |x, y| {
+            let position = if axis == Axis::Horizontal { x } else { y };
+            if position % 20 == 7 {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        })
+    };
+    for (image, axis) in [
+        (ticks(200, 12, Axis::Horizontal), Axis::Horizontal),
+        (ticks(12, 200, Axis::Vertical), Axis::Vertical),
+    ] {
+        let (spacing, first) = detect_axis_scale(&image, axis).unwrap();
+        assert_eq!(spacing, 20.0);
+        // the tick's both edges stand out, the one before it or the one after
+        assert!((first - 7.0).abs() <= 1.0, "{}", first);
+    }
+    let blank = image::RgbaImage::from_pixel(200, 12, image::Rgba([255, 255, 255, 255]));
+    assert!(detect_axis_scale(&blank, Axis::Horizontal).is_none());
+}
+
+#[test]
+fn points_on_the_line_have_no_influence() {
+    let points = (0..6)
+        .map(|i| PointCoords::new(i as f32 * 10.0, -(2.0 * i as f32 * 10.0 + 5.0)))
+        .collect::<UniquePointBuf>();
+    for mode in [RegressionMode::Ols, RegressionMode::Tls] {
+        let mut line = ScreenLineSegment::new_from_buf(points.clone());
+        line.set_requested_fit(CurveFitKind::Linear);
+        line.set_regression_mode(mode);
+        line.transform_line(&PointTransform::new(0.5, 0.2, 10.0, -4.0));
+        for (_, influence) in line.regressor().influence_by_point() {
+            assert!(influence < 1e-3, "{:?}: {}", mode, influence);
+        }
+    }
+    let pair = (0..2)
+        .map(|i| PointCoords::new(i as f32, i as f32))
+        .collect::<UniquePointBuf>();
+    let influence = RegressionLineSegment::new(pair).influence_vector();
+    assert_eq!(influence, vec![0.0, 0.0]);
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "nalgebra")]
+
+use sci_shot::{PointCoords, PointTransform, Transformable};
+
+#[test]
+fn unit_scale_transform_round_trips() {
+    let angle = 0.3_f32;
+    let pt = PointTransform::new(angle.cos(), angle.sin(), 12.0, -3.5);
+    let back = PointTransform::from(pt.as_nalgebra_isometry());
+    assert!(pt.near_equals(&back, 1e-6));
+}
+
+#[test]
+fn isometry_matches_the_transform_on_y_up_points() {
+    let angle = -1.1_f32;
+    let pt = PointTransform::new(angle.cos(), angle.sin(), 2.0, 5.0);
+    let p = PointCoords::new(3.0, -4.0);
+    let expected = p.transform(&pt);
+    // the transform flips y before rotating, the isometry doesn't
+    let actual = pt.as_nalgebra_isometry() * nalgebra::Point2::new(3.0, 4.0);
+    assert!((expected.x.into_inner() - actual.x).abs() < 1e-5);
+    assert!((expected.y.into_inner() - actual.y).abs() < 1e-5);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "serde")]
+
+use sci_shot::{
+    export::export_all_lines_json, PointCoords, PointTransform, ScreenLineSegment, Transformable,
+    UniquePointBuf,
+};
+
+#[test]
+fn point_transform_round_trips() {
+    let pt = PointTransform::new(0.5, -0.25, 12.0, -3.5);
+    assert_eq!(
+        serde_json::from_str::<PointTransform>(&serde_json::to_string(&pt).unwrap()).unwrap(),
+        pt
+    );
+}
+
+#[test]
+fn point_coords_round_trip() {
+    let pt = PointCoords::new(1.25, -7.0);
+    assert_eq!(
+        serde_json::from_str::<PointCoords>(&serde_json::to_string(&pt).unwrap()).unwrap(),
+        pt
+    );
+}
+
+#[test]
+fn lines_export_to_json() {
+    let dir = std::env::temp_dir().join(format!("sci_shot_json_{}", std::process::id()));
+    let path = dir.join("lines.json");
+    let points = (0..5)
+        .map(|i| PointCoords::new(i as f32, 2.0 - 3.0 * i as f32))
+        .collect::<UniquePointBuf>();
+    // screen y points down, so the world line is y = 3x - 2
+    let line = ScreenLineSegment::new_from_buf(points);
+    export_all_lines_json(&[line], &PointTransform::identity(), &path).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let line = &json[0];
+    assert_eq!(line["id"], 0);
+    assert!((line["slope"].as_f64().unwrap() - 3.0).abs() < 1e-4);
+    assert!((line["intercept"].as_f64().unwrap() + 2.0).abs() < 1e-4);
+    assert_eq!(line["points"].as_array().unwrap().len(), 5);
+    assert_eq!(line["points"][1]["world_y"], 1.0);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn calibration_files_keep_the_pairs_and_refit_edits() {
+    let dir = std::env::temp_dir().join(format!("sci_shot_calibration_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("calibration.json");
+    let transform = PointTransform::new(0.5, 0.0, 1.0, 2.0);
+    let pairs = [PointCoords::new(0.0, 0.0), PointCoords::new(10.0, -4.0)]
+        .map(|screen| (screen, screen.transform(&transform)));
+    transform.save_to_file(&path, &pairs).unwrap();
+
+    let (loaded, loaded_pairs) = PointTransform::load_from_file(&path).unwrap();
+    assert!(loaded.near_equals(&transform, 1e-5));
+    assert_eq!(loaded_pairs, pairs);
+
+    // moving a world point by hand changes the transform that gets loaded
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    json["pairs"][1]["world"]["x"] = 11.0.into();
+    std::fs::write(&path, json.to_string()).unwrap();
+    let (edited, _) = PointTransform::load_from_file(&path).unwrap();
+    assert!(!edited.near_equals(&transform, 1e-3));
+    let _ = std::fs::remove_dir_all(dir);
+}